@@ -0,0 +1,111 @@
+//! Benchmarks of the dynamic programming on the problem of the repository, run by
+//! `cargo bench --features bench`.
+//!
+//! The DP is timed alone, without tracing back the selection nor bounding it, against the DP
+//! decoding the amounts of every state from its index as it did before the states were counted.
+
+use mkp::{Amounts, Format, Problem, UncheckedProblem};
+use std::{
+    hint::black_box,
+    time::{Duration, Instant},
+};
+
+/// How long each benchmark runs for, at least.
+const TIME: Duration = Duration::from_secs(3);
+
+fn bench(name: &str, problem: &Problem, dp: impl Fn(&Problem) -> Option<f64>) {
+    let start = Instant::now();
+    let mut runs = 0;
+    while start.elapsed() < TIME {
+        black_box(dp(black_box(problem)));
+        runs += 1;
+    }
+    let each = start.elapsed() / runs;
+    println!("{:<16} {:>12.3?} per run, {} runs", name, each, runs);
+}
+
+fn units(amounts: &Amounts) -> Vec<usize> {
+    match amounts {
+        Amounts::List(list) => list.iter().map(|&a| a as usize).collect(),
+        Amounts::Named(_) => unreachable!("the amounts of input.toml are listed"),
+    }
+}
+
+fn to_cost(bounds: &[usize], mut c: usize) -> Vec<usize> {
+    let mut costs = Vec::new();
+    for bound in bounds.iter().rev() {
+        let idx = c % (bound + 1);
+        c /= bound + 1;
+        costs.push(idx);
+    }
+    costs.reverse();
+    costs
+}
+
+fn validate_sub(bounds: &[usize], bound: &[usize], cost: &[usize]) -> Option<usize> {
+    let mut ans = 0;
+    for idx in 0..bound.len() {
+        if cost[idx] > bound[idx] {
+            return None;
+        } else {
+            let c = if idx + 1 < bounds.len() {
+                bounds[idx + 1]
+            } else {
+                0
+            };
+            ans += bound[idx] - cost[idx];
+            ans *= c + 1;
+        }
+    }
+    Some(ans)
+}
+
+/// The best value of the DP indexing its states as it used to, by the same passes, for the
+/// bounded things without minimum nor group of input.toml.
+fn baseline(problem: &Problem) -> Option<f64> {
+    let bounds = problem
+        .costs()
+        .iter()
+        .map(|&c| c as usize)
+        .collect::<Vec<_>>();
+    let states = bounds.iter().map(|b| b + 1).product::<usize>();
+    let mut dp = vec![0.0; states];
+    for thing in problem.things() {
+        let costs = units(&thing.costs);
+        let mut pass = |k: usize| {
+            let cost = costs.iter().map(|c| c * k).collect::<Vec<_>>();
+            let value = k as f64 * thing.value;
+            for c in (0..states).rev() {
+                let bound = to_cost(&bounds, c);
+                if let Some(idx) = validate_sub(&bounds, &bound, &cost) {
+                    let v = dp[idx] + value;
+                    if v > dp[c] {
+                        dp[c] = v;
+                    }
+                }
+            }
+        };
+        let mut num = thing.num.expect("the things of input.toml are bounded");
+        let mut k = 1;
+        while k < num {
+            pass(k);
+            num -= k;
+            k *= 2;
+        }
+        if num > 0 {
+            pass(num);
+        }
+    }
+    Some(dp[states - 1])
+}
+
+fn main() {
+    let problem = Format::Toml
+        .deserialize::<UncheckedProblem>(include_str!("../input.toml"))
+        .and_then(UncheckedProblem::check)
+        .expect("input.toml is a valid problem");
+    assert_eq!(baseline(&problem), mkp::dp_value(&problem, 1));
+    bench("baseline", &problem, baseline);
+    bench("dp", &problem, |problem| mkp::dp_value(problem, 1));
+    bench("dp, 4 jobs", &problem, |problem| mkp::dp_value(problem, 4));
+}
@@ -0,0 +1,521 @@
+use crate::{
+    problem::{ratio, tolerance},
+    solver::Stop,
+    Error, Problem, Result, Solution, TieBreak,
+};
+use std::{
+    cmp::Ordering,
+    mem,
+    sync::atomic::{self, AtomicU64, AtomicUsize},
+    thread,
+};
+
+/// The fewest subtrees the search is split into when run by several threads, so that they share
+/// the work evenly.
+const TASKS: usize = 64;
+
+/// Which selections the search keeps.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Keep {
+    /// The given number of best selections.
+    Best(usize),
+    /// Every optimal selection, up to the given number.
+    Optimal(usize),
+}
+
+/// Depth-first branch-and-bound over the number of copies of each thing.
+///
+/// Nodes are pruned with the LP relaxation of every single dimension: the fractional knapsack
+/// of the remaining things against the remaining capacity of that dimension, taking the
+/// tightest of them.
+///
+/// With several knapsacks, the copies of each thing are then spread over the knapsacks in
+/// every way that fits, and the bounds pool the remaining capacity of all knapsacks.
+///
+/// Several selections can be kept, so that the search enumerates the best alternatives or the
+/// ties of the optimum as well as the optimum itself. Selections of the same value are ranked by
+/// the tie-break policy if any, and in the order they are found otherwise.
+///
+/// The search can be stopped early, keeping the selections found so far.
+///
+/// Several threads search the subtrees below a fixed depth, each keeping its own selections,
+/// which are then merged in the order the subtrees are searched in alone. When a single
+/// selection or the optimal ones are kept, the threads share the best value found, only to
+/// prune the nodes strictly worse than it. The selections kept are thus the same whatever the
+/// number of threads.
+#[derive(Debug, Clone)]
+pub(crate) struct BranchAndBound<'a> {
+    problem: &'a Problem,
+    /// Things in branching order, by decreasing aggregated efficiency.
+    order: Vec<usize>,
+    /// The position of each thing in `order`.
+    position: Vec<usize>,
+    /// For each dimension, the positions in `order` by decreasing value per unit of cost.
+    by_ratio: Vec<Vec<usize>>,
+    /// The capacity left in each knapsack, a single one unless the problem has several.
+    remaining: Vec<Vec<usize>>,
+    /// Whether the minimum quantities are placed by the search, rather than taken out of the
+    /// capacity beforehand.
+    places_min: bool,
+    counts: Vec<usize>,
+    /// The copies of each thing in each knapsack, minimum quantities included.
+    assignment: Vec<Vec<usize>>,
+    /// The number of members taken in each group.
+    taken: Vec<usize>,
+    value: f64,
+    keep: Keep,
+    tie_break: Option<TieBreak>,
+    /// The selections kept so far with their values and assignments, best first.
+    best: Vec<(f64, Vec<usize>, Vec<Vec<usize>>)>,
+    stop: Stop,
+    /// The number of nodes visited, to check whether to stop every so many nodes.
+    nodes: usize,
+    stopped: bool,
+    /// The depth at which the nodes are recorded as subtrees rather than searched.
+    split: Option<usize>,
+    subtrees: Vec<Subtree>,
+    /// The best value found by any thread, as the bits of an `f64`.
+    shared: Option<&'a AtomicU64>,
+}
+
+/// A node of the search, to search the subtree below it on its own.
+#[derive(Debug, Clone)]
+struct Subtree {
+    depth: usize,
+    remaining: Vec<Vec<usize>>,
+    counts: Vec<usize>,
+    assignment: Vec<Vec<usize>>,
+    taken: Vec<usize>,
+    value: f64,
+}
+
+impl<'a> BranchAndBound<'a> {
+    pub(crate) fn new(
+        problem: &'a Problem,
+        keep: Keep,
+        tie_break: Option<TieBreak>,
+        stop: Stop,
+    ) -> Self {
+        let items = &problem.items;
+        let capacity = problem.costs.bounds();
+        let mut order = (0..items.len()).collect::<Vec<_>>();
+        order.sort_by(|&a, &b| {
+            problem
+                .efficiency(b)
+                .partial_cmp(&problem.efficiency(a))
+                .unwrap_or(Ordering::Equal)
+        });
+        let by_ratio = (0..capacity.len())
+            .map(|d| {
+                let ratio = |pos: usize| {
+                    let thing = &items[order[pos]];
+                    ratio(thing.value, thing.costs[d] as f64)
+                };
+                let mut positions = (0..order.len()).collect::<Vec<_>>();
+                positions
+                    .sort_by(|&a, &b| ratio(b).partial_cmp(&ratio(a)).unwrap_or(Ordering::Equal));
+                positions
+            })
+            .collect();
+        let mut position = vec![0; order.len()];
+        for (pos, &idx) in order.iter().enumerate() {
+            position[idx] = pos;
+        }
+        Self {
+            problem,
+            order,
+            position,
+            by_ratio,
+            remaining: if problem.bins.is_empty() {
+                vec![capacity.to_vec()]
+            } else {
+                problem.bins.clone()
+            },
+            places_min: !problem.bins.is_empty(),
+            counts: vec![0; items.len()],
+            assignment: vec![vec![0; problem.bins.len().max(1)]; items.len()],
+            taken: vec![0; problem.groups.len()],
+            value: 0.0,
+            keep,
+            tie_break,
+            best: Vec::new(),
+            stop,
+            nodes: 0,
+            stopped: false,
+            split: None,
+            subtrees: Vec::new(),
+            shared: None,
+        }
+    }
+
+    /// The most copies of `thing` worth taking that fit in the remaining capacity.
+    ///
+    /// Things worth nothing are left out when a single selection is searched for, but not when
+    /// enumerating, as taking them gives other selections of the same or a lower value, though
+    /// a single copy of those that cost nothing and have no limit.
+    fn max_count(&self, thing: usize) -> usize {
+        let single = matches!(self.keep, Keep::Best(1)) && self.tie_break.is_none();
+        let thing = &self.problem.items[thing];
+        let worth = match thing
+            .group
+            .map(|g| (&self.problem.groups[g], self.taken[g]))
+        {
+            Some((_, taken)) if taken > 0 => 0,
+            _ if thing.conflicts.iter().any(|&other| self.is_taken(other)) => 0,
+            _ if thing.value > 0.0 => usize::MAX,
+            _ if !single && thing.num.is_none() && thing.costs.iter().all(|&c| c == 0) => 1,
+            _ if !single => usize::MAX,
+            Some((group, _)) if group.exact => 1,
+            _ if !thing.required_by.is_empty() => 1,
+            _ => 0,
+        };
+        let fit = self
+            .remaining
+            .iter()
+            .map(|remaining| fit(remaining, &thing.costs))
+            .fold(0, usize::saturating_add);
+        let fit = if self.places_min {
+            fit.saturating_sub(thing.min)
+        } else {
+            fit
+        };
+        fit.min(thing.num.unwrap_or(usize::MAX)).min(worth)
+    }
+
+    /// Whether `thing` is known to be taken, by its minimum quantity or by a decision.
+    fn is_taken(&self, thing: usize) -> bool {
+        self.problem.items[thing].min > 0 || self.counts[thing] > 0
+    }
+
+    /// Whether taking `k` copies of `thing` beyond its minimum, at the given depth, respects
+    /// the conflicts and requirements with the things known to be taken or not.
+    fn is_consistent(&self, thing: usize, k: usize, depth: usize) -> bool {
+        let item = &self.problem.items[thing];
+        if item.min > 0 || k > 0 {
+            let decided = |other: usize| self.position[other] < depth;
+            !item.conflicts.iter().any(|&other| self.is_taken(other))
+                && item
+                    .requires
+                    .iter()
+                    .all(|&other| !decided(other) || self.is_taken(other))
+        } else {
+            !item.required_by.iter().any(|&other| self.is_taken(other))
+        }
+    }
+
+    /// An upper bound of the value reachable from the current node, when the things before
+    /// `depth` are fixed, less the amounts `used` of the capacity if any.
+    fn bound(&self, depth: usize, used: &[usize]) -> f64 {
+        let items = &self.problem.items;
+        let mut bound = f64::INFINITY;
+        for (d, positions) in self.by_ratio.iter().enumerate() {
+            let capacity = self.remaining.iter().map(|r| r[d]).sum::<usize>();
+            let mut capacity = capacity.saturating_sub(used.get(d).copied().unwrap_or(0)) as f64;
+            let mut value = 0.0;
+            for &pos in positions.iter().filter(|&&pos| pos >= depth) {
+                let idx = self.order[pos];
+                let thing = &items[idx];
+                if thing.value <= 0.0 {
+                    break;
+                }
+                let num = self.max_count(idx) as f64;
+                let cost = thing.costs[d] as f64 * num;
+                if cost <= capacity {
+                    capacity -= cost;
+                    value += thing.value * num;
+                } else {
+                    value += thing.value * capacity / thing.costs[d] as f64;
+                    break;
+                }
+            }
+            bound = bound.min(value);
+        }
+        self.value + bound
+    }
+
+    /// Whether the node with the given upper bound cannot lead to a kept selection.
+    fn prune(&self, bound: f64) -> bool {
+        if let Some(shared) = self.shared {
+            let shared = f64::from_bits(shared.load(atomic::Ordering::Relaxed));
+            if bound < shared - tolerance(shared) {
+                return true;
+            }
+        }
+        let threshold = match self.keep {
+            Keep::Best(k) if self.best.len() < k => return false,
+            Keep::Best(k) => self.best[k - 1].0,
+            Keep::Optimal(_) if self.best.is_empty() => return false,
+            Keep::Optimal(_) => self.best[0].0,
+        };
+        match (self.keep, self.tie_break) {
+            (Keep::Best(_), None) => bound <= threshold,
+            _ => bound < threshold - tolerance(threshold),
+        }
+    }
+
+    /// Rank two selections, `Ordering::Less` if `a` is better.
+    fn compare(&self, a: (f64, &[usize]), b: (f64, &[usize])) -> Ordering {
+        if a.0 > b.0 + tolerance(b.0) {
+            Ordering::Less
+        } else if a.0 < b.0 - tolerance(b.0) {
+            Ordering::Greater
+        } else {
+            self.tie_break
+                .map_or(Ordering::Equal, |t| t.compare(self.problem, a.1, b.1))
+        }
+    }
+
+    /// Keep the current selection if it is good enough.
+    fn offer(&mut self) {
+        let limit = match self.keep {
+            Keep::Best(k) => k,
+            Keep::Optimal(limit) => {
+                if let Some(best) = self.best.first().map(|(v, _, _)| *v) {
+                    if self.value > best + tolerance(best) {
+                        self.best.clear();
+                    } else if self.value < best - tolerance(best) {
+                        return;
+                    }
+                }
+                limit
+            }
+        };
+        let current = (self.value, &self.counts[..]);
+        if let Some((value, counts, _)) = self.best.get(limit - 1) {
+            if self.compare(current, (*value, counts)) != Ordering::Less {
+                return;
+            }
+        }
+        // The same selection can be reached by several assignments to the knapsacks.
+        if self.places_min && self.best.iter().any(|(_, c, _)| c == &self.counts) {
+            return;
+        }
+        let pos = self
+            .best
+            .partition_point(|(v, c, _)| self.compare((*v, c), current) != Ordering::Greater);
+        let kept = (self.value, self.counts.clone(), self.assignment.clone());
+        self.best.insert(pos, kept);
+        self.best.truncate(limit);
+        if let Some(shared) = self.shared {
+            let best = self.best[0].0;
+            let _ =
+                shared.fetch_update(atomic::Ordering::Relaxed, atomic::Ordering::Relaxed, |s| {
+                    (best > f64::from_bits(s)).then(|| best.to_bits())
+                });
+        }
+    }
+
+    fn search(&mut self, depth: usize) {
+        if self.stopped {
+            return;
+        }
+        self.nodes += 1;
+        if self.nodes.is_multiple_of(1024) && self.stop.is_due() {
+            info!("stopped after {} nodes", self.nodes);
+            self.stopped = true;
+            return;
+        }
+        if self.split == Some(depth) {
+            self.subtrees.push(Subtree {
+                depth,
+                remaining: self.remaining.clone(),
+                counts: self.counts.clone(),
+                assignment: self.assignment.clone(),
+                taken: self.taken.clone(),
+                value: self.value,
+            });
+            return;
+        }
+        if depth == self.order.len() {
+            let groups = &self.problem.groups;
+            if groups
+                .iter()
+                .zip(&self.taken)
+                .all(|(g, t)| !g.exact || *t > 0)
+            {
+                self.offer();
+            }
+            return;
+        }
+        if self.prune(self.bound(depth, &[])) {
+            return;
+        }
+        let idx = self.order[depth];
+        let thing = &self.problem.items[idx];
+        let value = self.value;
+        // None of the counts of copies from `lo` to `hi` is worth more than `hi` copies with
+        // the capacity left by `lo`, so that the counts are halved until that bound prunes them
+        // rather than tried one by one, the larger first.
+        let mut counts = vec![(0, self.max_count(idx))];
+        while let Some((lo, hi)) = counts.pop() {
+            if self.stopped {
+                break;
+            }
+            let used = thing.costs.iter().map(|c| c * lo).collect::<Vec<_>>();
+            let gain = (thing.value * lo as f64).max(thing.value * hi as f64);
+            if self.prune(self.bound(depth + 1, &used) + gain) {
+                continue;
+            }
+            if lo < hi {
+                let mid = lo + (hi - lo) / 2;
+                counts.push((lo, mid));
+                counts.push((mid + 1, hi));
+                continue;
+            }
+            let k = lo;
+            if !self.is_consistent(idx, k, depth) {
+                continue;
+            }
+            self.counts[idx] = k;
+            self.value = value + thing.value * k as f64;
+            let group = thing.group.filter(|_| k > 0);
+            if let Some(g) = group {
+                self.taken[g] += 1;
+            }
+            let copies = if self.places_min { k + thing.min } else { k };
+            self.place(depth, 0, copies);
+            if let Some(g) = group {
+                self.taken[g] -= 1;
+            }
+            self.counts[idx] = 0;
+            self.value = value;
+        }
+    }
+
+    /// Spread the `copies` left of the thing at `depth` over the knapsacks from `bin` on, in
+    /// every way that fits, and search on from each of them.
+    fn place(&mut self, depth: usize, bin: usize, copies: usize) {
+        if bin == self.remaining.len() {
+            if copies == 0 {
+                self.search(depth + 1);
+            }
+            return;
+        }
+        let idx = self.order[depth];
+        let costs = &self.problem.items[idx].costs;
+        let later = self.remaining[bin + 1..]
+            .iter()
+            .map(|remaining| fit(remaining, costs))
+            .fold(0, usize::saturating_add);
+        let most = fit(&self.remaining[bin], costs).min(copies);
+        for q in (copies.saturating_sub(later)..=most).rev() {
+            if self.stopped {
+                break;
+            }
+            for (r, c) in self.remaining[bin].iter_mut().zip(costs) {
+                *r -= c * q;
+            }
+            self.assignment[idx][bin] = q;
+            self.place(depth, bin + 1, copies - q);
+            for (r, c) in self.remaining[bin].iter_mut().zip(costs) {
+                *r += c * q;
+            }
+        }
+        self.assignment[idx][bin] = 0;
+    }
+
+    /// Search the subtrees below the shallowest depth with enough of them, over `jobs` threads.
+    fn search_parallel(&mut self, jobs: usize) {
+        let mut subtrees = Vec::new();
+        for depth in 1..self.order.len() {
+            self.split = Some(depth);
+            self.search(0);
+            subtrees = mem::take(&mut self.subtrees);
+            if subtrees.len() >= TASKS {
+                break;
+            }
+        }
+        self.split = None;
+        if subtrees.is_empty() {
+            return self.search(0);
+        }
+        let shared = AtomicU64::new(f64::NEG_INFINITY.to_bits());
+        let next = AtomicUsize::new(0);
+        let mut found = thread::scope(|scope| {
+            let threads = (0..jobs.min(subtrees.len()))
+                .map(|_| {
+                    let mut search = self.clone();
+                    if let Keep::Best(1) | Keep::Optimal(_) = self.keep {
+                        search.shared = Some(&shared);
+                    }
+                    let (subtrees, next) = (&subtrees, &next);
+                    scope.spawn(move || {
+                        let mut found = Vec::new();
+                        loop {
+                            let i = next.fetch_add(1, atomic::Ordering::Relaxed);
+                            let subtree = match subtrees.get(i) {
+                                Some(subtree) => subtree.clone(),
+                                None => return found,
+                            };
+                            search.remaining = subtree.remaining;
+                            search.counts = subtree.counts;
+                            search.assignment = subtree.assignment;
+                            search.taken = subtree.taken;
+                            search.value = subtree.value;
+                            search.stopped = false;
+                            search.search(subtree.depth);
+                            found.push((i, mem::take(&mut search.best), search.stopped));
+                            if search.stopped {
+                                return found;
+                            }
+                        }
+                    })
+                })
+                .collect::<Vec<_>>();
+            threads
+                .into_iter()
+                .flat_map(|thread| thread.join().expect("a branch-and-bound thread panicked"))
+                .collect::<Vec<_>>()
+        });
+        found.sort_by_key(|(i, _, _)| *i);
+        for (_, best, stopped) in found {
+            self.stopped |= stopped;
+            for (value, counts, assignment) in best {
+                self.value = value;
+                self.counts = counts;
+                self.assignment = assignment;
+                self.offer();
+            }
+        }
+    }
+
+    /// The selections to keep, best first, which are not proven if the search is stopped,
+    /// searched by `jobs` threads.
+    pub(crate) fn solve(mut self, jobs: usize) -> Result<Vec<Solution>> {
+        if let Keep::Best(0) | Keep::Optimal(0) = self.keep {
+            return Ok(Vec::new());
+        }
+        if jobs <= 1 {
+            self.search(0);
+        } else {
+            self.search_parallel(jobs);
+        }
+        if self.stopped && self.best.is_empty() {
+            return Err(Error::Stopped);
+        }
+        let problem = self.problem;
+        Ok(self
+            .best
+            .iter()
+            .map(|(value, counts, assignment)| {
+                let mut solution = Solution::new(problem, *value, counts);
+                solution.proven = !self.stopped;
+                if problem.knapsacks.is_empty() {
+                    solution
+                } else {
+                    solution.with_assignment(problem, assignment)
+                }
+            })
+            .collect())
+    }
+}
+
+/// The most copies of a thing of the given costs that fit in the remaining capacity.
+fn fit(remaining: &[usize], costs: &[usize]) -> usize {
+    costs
+        .iter()
+        .zip(remaining)
+        .filter(|(&c, _)| c > 0)
+        .map(|(c, r)| r / c)
+        .fold(usize::MAX, usize::min)
+}
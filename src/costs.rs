@@ -0,0 +1,137 @@
+/// The capacity of each dimension, or its requirement in a covering problem, also used to index
+/// the state space of the DP table.
+///
+/// The states are numbered with the first dimension most significant, so that taking a cost out
+/// of a state moves its index back by the offset of the cost, as long as no dimension goes below
+/// zero.
+#[derive(Debug, Clone)]
+pub(crate) struct Costs {
+    bounds: Vec<usize>,
+    /// The difference of index between states one unit apart in each dimension.
+    strides: Vec<usize>,
+}
+
+impl Costs {
+    pub(crate) fn new(bounds: Vec<usize>) -> Self {
+        let mut strides = vec![1usize; bounds.len()];
+        for d in (1..bounds.len()).rev() {
+            strides[d - 1] = strides[d].saturating_mul(bounds[d].saturating_add(1));
+        }
+        Self { bounds, strides }
+    }
+
+    pub(crate) fn bounds(&self) -> &[usize] {
+        &self.bounds
+    }
+
+    /// The number of states, or `None` if it overflows `usize`.
+    pub(crate) fn states(&self) -> Option<usize> {
+        self.bounds
+            .iter()
+            .try_fold(1usize, |acc, c| acc.checked_mul(c.checked_add(1)?))
+    }
+
+    /// The index of the last state.
+    ///
+    /// # Panics
+    /// Panics if the number of states overflows `usize`, see [`Costs::states`].
+    pub(crate) fn end(&self) -> usize {
+        self.states().expect("the state space overflows usize") - 1
+    }
+
+    /// The index of the state of the given amounts, which is also the offset between the
+    /// states these amounts apart.
+    pub(crate) fn offset(&self, cost: &[usize]) -> usize {
+        cost.iter()
+            .zip(&self.strides)
+            .fold(0, |acc, (c, s)| acc.saturating_add(c.saturating_mul(*s)))
+    }
+
+    pub(crate) fn to_cost(&self, mut c: usize) -> Vec<usize> {
+        let mut costs = Vec::new();
+        for bound in self.bounds.iter().rev() {
+            let idx = c % (bound + 1);
+            c /= bound + 1;
+            costs.push(idx);
+        }
+        costs.reverse();
+        costs
+    }
+
+    /// The amounts of the consecutive states from `c` on.
+    pub(crate) fn counter(&self, c: usize) -> Counter<'_> {
+        Counter {
+            bounds: &self.bounds,
+            digits: self.to_cost(c),
+        }
+    }
+
+    /// The index of `bound - k * cost`, where `c` is the index of `bound` and `offset` that of
+    /// `cost`, or `None` if any dimension goes below zero.
+    pub(crate) fn checked_sub(
+        &self,
+        c: usize,
+        bound: &[usize],
+        cost: &[usize],
+        offset: usize,
+        k: usize,
+    ) -> Option<usize> {
+        if bound
+            .iter()
+            .zip(cost)
+            .all(|(&b, &x)| x.checked_mul(k).is_some_and(|xk| xk <= b))
+        {
+            Some(c - offset * k)
+        } else {
+            None
+        }
+    }
+
+    /// The index of `bound - k * cost`, each dimension clamped at zero.
+    ///
+    /// Used by covering problems, whose states are the requirements still to be met.
+    pub(crate) fn saturating_sub(&self, bound: &[usize], cost: &[usize], k: usize) -> usize {
+        bound
+            .iter()
+            .zip(cost)
+            .zip(&self.strides)
+            .map(|((&b, &x), s)| b.saturating_sub(x.saturating_mul(k)) * s)
+            .sum()
+    }
+}
+
+/// The amount of each dimension of consecutive states, counted up in place rather than decoded
+/// from the index of each state.
+#[derive(Debug)]
+pub(crate) struct Counter<'a> {
+    bounds: &'a [usize],
+    digits: Vec<usize>,
+}
+
+impl Counter<'_> {
+    pub(crate) fn get(&self) -> &[usize] {
+        &self.digits
+    }
+
+    /// Move on to the next state, back to the first after the last.
+    pub(crate) fn advance(&mut self) {
+        for (digit, bound) in self.digits.iter_mut().zip(self.bounds).rev() {
+            if *digit < *bound {
+                *digit += 1;
+                return;
+            }
+            *digit = 0;
+        }
+    }
+
+    /// Move back to the previous state, on to the last before the first.
+    pub(crate) fn retreat(&mut self) {
+        for (digit, bound) in self.digits.iter_mut().zip(self.bounds).rev() {
+            if *digit > 0 {
+                *digit -= 1;
+                return;
+            }
+            *digit = *bound;
+        }
+    }
+}
@@ -0,0 +1,114 @@
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fmt};
+
+/// An amount of each dimension, such as a capacity or the costs of a thing.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Amounts {
+    /// The amount of each dimension, in order.
+    List(Vec<f64>),
+    /// The amount of each dimension by name, for problems naming their dimensions.
+    Named(BTreeMap<String, f64>),
+}
+
+impl Default for Amounts {
+    fn default() -> Self {
+        Self::List(Vec::new())
+    }
+}
+
+impl From<Vec<f64>> for Amounts {
+    fn from(list: Vec<f64>) -> Self {
+        Self::List(list)
+    }
+}
+
+impl From<BTreeMap<String, f64>> for Amounts {
+    fn from(named: BTreeMap<String, f64>) -> Self {
+        Self::Named(named)
+    }
+}
+
+impl Amounts {
+    /// Whether no amount is given.
+    pub fn is_empty(&self) -> bool {
+        match self {
+            Self::List(list) => list.is_empty(),
+            Self::Named(named) => named.is_empty(),
+        }
+    }
+
+    /// The amounts in the order of the dimensions.
+    ///
+    /// # Panics
+    /// Panics if the amounts are named, which they no longer are once the problem is checked.
+    pub(crate) fn list(&self) -> &[f64] {
+        match self {
+            Self::List(list) => list,
+            Self::Named(_) => unreachable!("named amounts are resolved when checking the problem"),
+        }
+    }
+
+    /// Each amount given with its dimension.
+    pub(crate) fn entries(&self) -> Vec<(Dimension, f64)> {
+        match self {
+            Self::List(list) => list
+                .iter()
+                .enumerate()
+                .map(|(d, &a)| (Dimension::Index(d), a))
+                .collect(),
+            Self::Named(named) => named
+                .iter()
+                .map(|(name, &a)| (Dimension::Name(name.clone()), a))
+                .collect(),
+        }
+    }
+
+    /// The amounts in the order of the given dimension names, `missing` for the names not
+    /// listed, or the list itself if the amounts are not named.
+    pub(crate) fn resolve(&self, names: &[String], missing: f64) -> Vec<f64> {
+        match self {
+            Self::List(list) => list.clone(),
+            Self::Named(named) => names
+                .iter()
+                .map(|name| named.get(name).copied().unwrap_or(missing))
+                .collect(),
+        }
+    }
+
+    /// The amounts of a list named after the dimensions, if they are named.
+    pub(crate) fn name(list: Vec<f64>, names: &[String]) -> Self {
+        if names.is_empty() {
+            Self::List(list)
+        } else {
+            Self::Named(names.iter().cloned().zip(list).collect())
+        }
+    }
+}
+
+/// A dimension, by name when the problem names its dimensions and by index otherwise.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Dimension {
+    Index(usize),
+    Name(String),
+}
+
+impl Dimension {
+    /// The `d`-th of the given dimension names, or its index if the dimensions are not named.
+    pub(crate) fn of(d: usize, names: &[String]) -> Self {
+        match names.get(d) {
+            Some(name) => Self::Name(name.clone()),
+            None => Self::Index(d),
+        }
+    }
+}
+
+impl fmt::Display for Dimension {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Index(d) => write!(f, "{}", d),
+            Self::Name(name) => write!(f, "`{}`", name),
+        }
+    }
+}
@@ -0,0 +1,351 @@
+use crate::{Mode, Problem, Solution};
+use std::thread;
+
+/// The fewest states updated by a thread, below which splitting a pass costs more than it saves.
+const MIN_CHUNK: usize = 1 << 16;
+
+/// Dense dynamic programming over every state of the cost space.
+///
+/// When covering, the states are the requirements still to be met and the values are negated,
+/// so that the same passes maximise the opposite of the value.
+///
+/// Rather than recording the copies taken by every pass at every state, the selection is traced
+/// back by comparing the tables before and after each pass. The tables are replayed from the
+/// middle of the passes, divide-and-conquer, so that only a few of them are held at once, as
+/// many as fit in the memory budget.
+///
+/// The passes taking at most one chunk of copies of each state split the states over several
+/// threads, reading a copy of the table before the pass, so that the result does not depend on
+/// them.
+#[derive(Debug)]
+pub(crate) struct Dp<'a> {
+    problem: &'a Problem,
+    cover: bool,
+    passes: Vec<Pass>,
+    /// The offset of the index of a state by the costs of each item.
+    offsets: Vec<usize>,
+    /// The most tables held at once.
+    tables: usize,
+    jobs: usize,
+}
+
+/// A pass of the DP over the whole table.
+#[derive(Debug, Clone, Copy)]
+enum Pass {
+    /// Take the given copies of an item at once, or none.
+    Chunk { item: usize, copies: usize },
+    /// Take any number of copies of an item without limit.
+    Complete(usize),
+    /// Take one member of a group in any quantity, or none unless the group is exact.
+    Group(usize),
+}
+
+impl<'a> Dp<'a> {
+    pub(crate) fn new(problem: &'a Problem, max_memory: usize, jobs: usize) -> Self {
+        let passes = passes(problem);
+        let table = (problem.costs.end() + 1) * std::mem::size_of::<f64>();
+        let tables = (max_memory / table.max(1)).max(least_tables(&passes));
+        Self {
+            problem,
+            cover: problem.mode == Mode::Cover,
+            passes,
+            offsets: problem
+                .items
+                .iter()
+                .map(|item| problem.costs.offset(&item.costs))
+                .collect(),
+            tables,
+            jobs: jobs.max(1),
+        }
+    }
+
+    /// The state reached from the state `c` of amounts `bound` by taking `k` copies of `item`,
+    /// `None` if they do not fit.
+    fn sub(&self, c: usize, bound: &[usize], item: usize, k: usize) -> Option<usize> {
+        let costs = &self.problem.costs;
+        let cost = &self.problem.items[item].costs;
+        if self.cover {
+            Some(costs.saturating_sub(bound, cost, k))
+        } else {
+            costs.checked_sub(c, bound, cost, self.offsets[item], k)
+        }
+    }
+
+    /// The value maximised by the passes for a thing of the given value.
+    fn gain(&self, value: f64) -> f64 {
+        if self.cover {
+            -value
+        } else {
+            value
+        }
+    }
+
+    /// The bytes needed by the tables held at once, or `None` on overflow.
+    pub(crate) fn memory(problem: &Problem) -> Option<usize> {
+        problem
+            .costs
+            .states()?
+            .checked_mul(std::mem::size_of::<f64>())?
+            .checked_mul(least_tables(&passes(problem)))
+    }
+
+    /// The table before any pass.
+    fn start(&self) -> Vec<f64> {
+        let mut dp = vec![0.0; self.problem.costs.end() + 1];
+        if self.cover {
+            // Only the state with nothing left to meet is reachable without taking anything.
+            dp[1..].fill(f64::NEG_INFINITY);
+        }
+        dp
+    }
+
+    fn apply(&self, pass: Pass, dp: &mut [f64]) {
+        match pass {
+            Pass::Chunk { item, copies } => self.zero_one_pack(item, copies, dp),
+            Pass::Complete(item) => self.complete_pack(item, dp),
+            Pass::Group(group) => self.group_pack(group, dp),
+        }
+    }
+
+    /// Set each state of `dp` to `update` of the table before the pass, the index of the state
+    /// and its amounts, splitting the states over the threads.
+    ///
+    /// A state is only updated from itself and states of lower index, so that a pass left to a
+    /// single thread sweeps the table in place from the last state down, and only a split pass
+    /// reads a copy of the table.
+    fn update(&self, dp: &mut [f64], update: impl Fn(&[f64], usize, &[usize]) -> f64 + Sync) {
+        let costs = &self.problem.costs;
+        let chunk = dp.len().div_ceil(self.jobs).max(MIN_CHUNK);
+        if chunk >= dp.len() {
+            let mut counter = costs.counter(dp.len() - 1);
+            for c in (0..dp.len()).rev() {
+                dp[c] = update(dp, c, counter.get());
+                counter.retreat();
+            }
+            return;
+        }
+        let prev = dp.to_vec();
+        let run = |start: usize, part: &mut [f64]| {
+            let mut counter = costs.counter(start);
+            for (c, x) in (start..).zip(part.iter_mut()) {
+                *x = update(&prev, c, counter.get());
+                counter.advance();
+            }
+        };
+        thread::scope(|scope| {
+            for (i, part) in dp.chunks_mut(chunk).enumerate() {
+                let run = &run;
+                scope.spawn(move || run(i * chunk, part));
+            }
+        });
+    }
+
+    fn zero_one_pack(&self, item: usize, k: usize, dp: &mut [f64]) {
+        let value = self.gain(k as f64 * self.problem.items[item].value);
+        self.update(dp, |prev, c, bound| match self.sub(c, bound, item, k) {
+            Some(idx) if prev[idx] + value > prev[c] => prev[idx] + value,
+            _ => prev[c],
+        });
+    }
+
+    fn complete_pack(&self, item: usize, dp: &mut [f64]) {
+        let value = self.gain(self.problem.items[item].value);
+        let mut counter = self.problem.costs.counter(0);
+        for c in 0..dp.len() {
+            if let Some(idx) = self.sub(c, counter.get(), item, 1) {
+                let v = dp[idx] + value;
+                if v > dp[c] {
+                    dp[c] = v;
+                }
+            }
+            counter.advance();
+        }
+    }
+
+    /// The copies of a group member worth trying, from the state `c` of amounts `bound` of the
+    /// table `prev`, with the state they reach and the value they give.
+    fn copies<'b>(
+        &'b self,
+        m: usize,
+        c: usize,
+        bound: &'b [usize],
+        prev: &'b [f64],
+    ) -> impl Iterator<Item = (usize, usize, f64)> + 'b {
+        let item = &self.problem.items[m];
+        let free = item.costs.iter().all(|c| *c == 0);
+        let num = item.num.unwrap_or(if free { 1 } else { usize::MAX });
+        // Free copies only add to the value when packing, so that all of them are taken.
+        let first = if free && !self.cover { num.max(1) } else { 1 };
+        let mut last = None;
+        (first..=num).map_while(move |k| {
+            let idx = match self.sub(c, bound, m, k) {
+                // When covering, a copy that meets nothing more only adds to the value.
+                Some(idx) if !(self.cover && last == Some(idx)) => idx,
+                _ => return None,
+            };
+            last = Some(idx);
+            Some((k, idx, prev[idx] + k as f64 * self.gain(item.value)))
+        })
+    }
+
+    /// Pack at most one of the members of the group, or exactly one if it is exact, in any
+    /// quantity.
+    fn group_pack(&self, group: usize, dp: &mut [f64]) {
+        let group = &self.problem.groups[group];
+        self.update(dp, |prev, c, bound| {
+            let mut best = if group.exact {
+                f64::NEG_INFINITY
+            } else {
+                prev[c]
+            };
+            for &m in group.members.iter() {
+                for (_, _, v) in self.copies(m, c, bound, prev) {
+                    if v > best {
+                        best = v;
+                    }
+                }
+            }
+            best
+        });
+    }
+
+    /// Record in `chosen` the copies the pass took to reach the state `c` of the table `after`
+    /// from the table `before`, returning the state they were taken from.
+    fn back(
+        &self,
+        pass: Pass,
+        before: &[f64],
+        after: &[f64],
+        mut c: usize,
+        chosen: &mut [usize],
+    ) -> usize {
+        let problem = self.problem;
+        let costs = &problem.costs;
+        // A state is only updated by a pass to a strictly better value.
+        let unchanged = after[c] == before[c];
+        match pass {
+            Pass::Chunk { .. } if unchanged => c,
+            Pass::Chunk { item, copies } => {
+                chosen[item] += copies;
+                self.sub(c, &costs.to_cost(c), item, copies)
+                    .expect("the copies were taken from a valid state")
+            }
+            Pass::Complete(item) => {
+                // Each copy is taken from a state updated earlier by the same pass.
+                while after[c] != before[c] {
+                    chosen[item] += 1;
+                    let idx = self
+                        .sub(c, &costs.to_cost(c), item, 1)
+                        .expect("the copy was taken from a valid state");
+                    if idx == c {
+                        break;
+                    }
+                    c = idx;
+                }
+                c
+            }
+            Pass::Group(group) if unchanged && !problem.groups[group].exact => c,
+            Pass::Group(group) => {
+                let bound = costs.to_cost(c);
+                for &m in problem.groups[group].members.iter() {
+                    for (k, idx, v) in self.copies(m, c, &bound, before) {
+                        if v == after[c] {
+                            chosen[m] += k;
+                            return idx;
+                        }
+                    }
+                }
+                unreachable!("the state of an exact group is reached by one of its members")
+            }
+        }
+    }
+
+    /// Trace back the `passes` applied to the table `before` from the state `c` after them,
+    /// while `held` other tables are held, returning the state before them.
+    fn trace(
+        &self,
+        passes: &[Pass],
+        before: Vec<f64>,
+        c: usize,
+        held: usize,
+        chosen: &mut [usize],
+    ) -> usize {
+        // The tables after each pass, plus the copy of the table made by a pass.
+        if held + passes.len() + 2 <= self.tables {
+            let mut tables = vec![before];
+            for &pass in passes {
+                let mut next = tables[tables.len() - 1].clone();
+                self.apply(pass, &mut next);
+                tables.push(next);
+            }
+            return passes.iter().enumerate().rev().fold(c, |c, (i, &pass)| {
+                self.back(pass, &tables[i], &tables[i + 1], c, chosen)
+            });
+        }
+        let (first, second) = passes.split_at(passes.len() / 2);
+        let mut middle = before.clone();
+        for &pass in first {
+            self.apply(pass, &mut middle);
+        }
+        let c = self.trace(second, middle, c, held + 1, chosen);
+        self.trace(first, before, c, held, chosen)
+    }
+
+    /// The best value after every pass, without tracing back the selection, `None` if no
+    /// selection satisfies the groups.
+    pub(crate) fn value(&self) -> Option<f64> {
+        let mut dp = self.start();
+        for &pass in self.passes.iter() {
+            self.apply(pass, &mut dp);
+        }
+        match dp[self.problem.costs.end()] {
+            f64::NEG_INFINITY => None,
+            value if self.cover => Some(0.0 - value),
+            value => Some(value),
+        }
+    }
+
+    /// Solve the problem, `None` if no selection satisfies the groups.
+    pub(crate) fn solve(self) -> Option<Solution> {
+        let problem = self.problem;
+        let value = self.value()?;
+        let end = problem.costs.end();
+        let mut chosen = vec![0; problem.items.len()];
+        self.trace(&self.passes, self.start(), end, 0, &mut chosen);
+        Some(Solution::new(problem, value, &chosen))
+    }
+}
+
+/// The passes solving the problem: the items in chunks of doubling size, then the groups.
+fn passes(problem: &Problem) -> Vec<Pass> {
+    let mut passes = Vec::new();
+    for (item, thing) in problem.items.iter().enumerate() {
+        if thing.group.is_some() {
+            continue;
+        }
+        let mut num = match thing.num {
+            Some(num) => num,
+            None => {
+                passes.push(Pass::Complete(item));
+                continue;
+            }
+        };
+        let mut k = 1;
+        while k < num {
+            passes.push(Pass::Chunk { item, copies: k });
+            num -= k;
+            k *= 2;
+        }
+        if num > 0 {
+            passes.push(Pass::Chunk { item, copies: num });
+        }
+    }
+    passes.extend((0..problem.groups.len()).map(Pass::Group));
+    passes
+}
+
+/// The fewest tables held at once to trace back the passes: one per halving of them, the two
+/// around the last pass and the copy of the table made by a pass.
+fn least_tables(passes: &[Pass]) -> usize {
+    passes.len().next_power_of_two().trailing_zeros() as usize + 3
+}
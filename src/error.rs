@@ -0,0 +1,236 @@
+use crate::{Algorithm, Dimension, Relation};
+use thiserror::Error;
+
+/// Errors produced while building or solving a problem.
+#[derive(Debug, Error)]
+pub enum Error {
+    /// The problem is not valid.
+    #[error("invalid problem:{}", list(.0))]
+    Invalid(Vec<ValidationError>),
+
+    /// The DP table does not fit in the memory budget.
+    #[error(
+        "dp needs {} bytes, which exceeds the limit of {max_memory} bytes",
+        required.map_or_else(|| format!("more than {}", usize::MAX), |r| r.to_string())
+    )]
+    OutOfMemory {
+        /// The bytes needed, `None` if it overflows `usize`.
+        required: Option<usize>,
+        max_memory: usize,
+    },
+
+    /// No selection satisfies the constraints of the problem.
+    #[error("no selection satisfies the problem")]
+    NoSolution,
+
+    /// A Pareto front is asked for but the things have no objectives.
+    #[error("no thing has objectives to compute the Pareto front of")]
+    NoObjectives,
+
+    /// The search was stopped before finding any selection.
+    #[error("stopped before any selection satisfying the problem was found")]
+    Stopped,
+
+    /// The algorithm cannot solve the kind of problem asked for.
+    #[error("{algorithm} does not support {feature}")]
+    Unsupported {
+        algorithm: Algorithm,
+        feature: &'static str,
+    },
+
+    /// The name of a serialization format is not recognized.
+    #[error("unknown format `{0}`, expected one of: toml, json")]
+    UnknownFormat(String),
+
+    /// The name of an algorithm is not recognized.
+    #[error("unknown algorithm `{0}`, expected one of: auto, dp, sparse-dp, branch-and-bound, greedy, local-search")]
+    UnknownAlgorithm(String),
+
+    /// The name of a tie-break policy is not recognized.
+    #[error("unknown tie-break `{0}`, expected one of: fewer-items, less-resource, lexicographic")]
+    UnknownTieBreak(String),
+
+    #[error(transparent)]
+    TomlDe(#[from] toml::de::Error),
+
+    #[error(transparent)]
+    TomlSer(#[from] toml::ser::Error),
+
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// A reason why a problem is not valid.
+///
+/// Things and knapsacks are located by their `index` in their list and their `name`.
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum ValidationError {
+    /// The problem declares no cost dimension.
+    #[error("must contain at least one cost")]
+    NoCosts,
+
+    /// The problem declares both a single capacity and several knapsacks.
+    #[error("must contain either costs or knapsacks, not both")]
+    CostsAndKnapsacks,
+
+    /// A covering problem declares knapsacks.
+    #[error("knapsacks cannot be covered, only packed")]
+    CoverKnapsacks,
+
+    /// The capacity of a dimension is zero.
+    #[error("capacity of dimension {dimension} is zero")]
+    ZeroCapacity { dimension: Dimension },
+
+    /// The capacity of a dimension is negative or not a number.
+    #[error("capacity of dimension {dimension} must be non-negative, found {capacity}")]
+    InvalidCapacity { dimension: Dimension, capacity: f64 },
+
+    /// The scale does not match the dimensions of the problem.
+    #[error("expected {expected} scales, found {actual}")]
+    ScaleMismatch { expected: usize, actual: usize },
+
+    /// A scale is not a positive number.
+    #[error("scale of dimension {dimension} must be positive, found {scale}")]
+    InvalidScale { dimension: Dimension, scale: f64 },
+
+    /// Amounts are given as a list although the dimensions of the problem are named.
+    #[error("{owner}: must be given by dimension name, as the dimensions are named")]
+    PositionalCosts { owner: String },
+
+    /// Amounts are given for a dimension the problem does not have.
+    #[error("{owner}: unknown dimension `{dimension}`")]
+    UnknownDimension { owner: String, dimension: String },
+
+    /// Two knapsacks have the same name.
+    #[error("knapsack #{index} `{name}`: name already used by knapsack #{first}")]
+    DuplicateKnapsack {
+        index: usize,
+        name: String,
+        first: usize,
+    },
+
+    /// The capacities of a knapsack do not match the dimensions of the problem.
+    #[error("knapsack #{index} `{name}`: expected {expected} costs, found {actual}")]
+    KnapsackCostsMismatch {
+        index: usize,
+        name: String,
+        expected: usize,
+        actual: usize,
+    },
+
+    /// A capacity of a knapsack is negative or not a number.
+    #[error(
+        "knapsack #{index} `{name}`: capacity of dimension {dimension} must be non-negative, found {capacity}"
+    )]
+    InvalidKnapsackCapacity {
+        index: usize,
+        name: String,
+        dimension: Dimension,
+        capacity: f64,
+    },
+
+    /// Two things have the same name.
+    #[error("thing #{index} `{name}`: name already used by thing #{first}")]
+    DuplicateName {
+        index: usize,
+        name: String,
+        first: usize,
+    },
+
+    /// The value of a thing is negative or not a number.
+    #[error("thing #{index} `{name}`: value must be non-negative, found {value}")]
+    InvalidValue {
+        index: usize,
+        name: String,
+        value: f64,
+    },
+
+    /// The value of a thing for an objective is negative or not a number.
+    #[error(
+        "thing #{index} `{name}`: objective `{objective}` must be non-negative, found {value}"
+    )]
+    InvalidObjective {
+        index: usize,
+        name: String,
+        objective: String,
+        value: f64,
+    },
+
+    /// The costs of a thing do not match the dimensions of the problem.
+    #[error("thing #{index} `{name}`: expected {expected} costs, found {actual}")]
+    CostsMismatch {
+        index: usize,
+        name: String,
+        expected: usize,
+        actual: usize,
+    },
+
+    /// A cost of a thing is negative or not a number.
+    #[error(
+        "thing #{index} `{name}`: cost of dimension {dimension} must be non-negative, found {cost}"
+    )]
+    InvalidCost {
+        index: usize,
+        name: String,
+        dimension: Dimension,
+        cost: f64,
+    },
+
+    /// A thing with unlimited copies is worth something but costs nothing.
+    #[error("thing #{index} `{name}`: unlimited copies of positive value at no cost")]
+    Unbounded { index: usize, name: String },
+
+    /// A thing requires more copies than are available.
+    #[error(
+        "thing #{index} `{name}`: requires at least {min} copies but only {num} are available"
+    )]
+    MinExceedsNum {
+        index: usize,
+        name: String,
+        min: usize,
+        num: usize,
+    },
+
+    /// A group limited to exactly one member has no member.
+    #[error("group `{group}` must have exactly one member taken but has no member")]
+    EmptyGroup { group: String },
+
+    /// Several members of a group have a minimum quantity.
+    #[error("group `{group}` allows one member but {} have a minimum quantity", things.join(", "))]
+    GroupMinimums { group: String, things: Vec<String> },
+
+    /// A thing refers to a thing that does not exist.
+    #[error("thing #{index} `{name}`: {relation} unknown thing `{reference}`")]
+    UnknownReference {
+        index: usize,
+        name: String,
+        relation: Relation,
+        reference: String,
+    },
+
+    /// Things require each other in a cycle.
+    #[error("requirements form a cycle: {} -> {}", things.join(" -> "), things[0])]
+    RequirementCycle { things: Vec<String> },
+
+    /// An amount does not fit in integral units once scaled.
+    #[error("{owner}: {amount} of dimension {dimension} is too large once scaled")]
+    AmountOverflow {
+        owner: String,
+        dimension: Dimension,
+        amount: f64,
+    },
+
+    /// The minimum quantities alone exceed a capacity.
+    #[error("minimum quantities need {required} of dimension {dimension} but its capacity is {capacity}")]
+    Infeasible {
+        dimension: Dimension,
+        required: f64,
+        capacity: f64,
+    },
+}
+
+fn list(errors: &[ValidationError]) -> String {
+    errors.iter().map(|e| format!("\n  - {}", e)).collect()
+}
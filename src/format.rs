@@ -0,0 +1,55 @@
+use crate::{Error, Result};
+use serde::{de::DeserializeOwned, Serialize};
+use std::{path::Path, str::FromStr};
+
+/// A serialization format for problems and solutions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Format {
+    #[default]
+    Toml,
+    Json,
+}
+
+impl Format {
+    /// Names accepted by [`Format::from_str`].
+    pub const VARIANTS: &'static [&'static str] = &["toml", "json"];
+
+    /// Guess the format from the extension of a file.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        path.extension()?.to_str()?.parse().ok()
+    }
+
+    /// Deserialize a value from a string in this format.
+    pub fn deserialize<T: DeserializeOwned>(self, s: &str) -> Result<T> {
+        match self {
+            Self::Toml => Ok(toml::from_str(s)?),
+            Self::Json => Ok(serde_json::from_str(s)?),
+        }
+    }
+
+    /// Serialize a value into a string in this format.
+    pub fn serialize<T: Serialize>(self, value: &T) -> Result<String> {
+        match self {
+            // TOML requires the plain values of a table before its subtables, whatever the order
+            // of the fields, which going through a `Value` takes care of.
+            Self::Toml => Ok(toml::to_string(&toml::Value::try_from(value)?)?),
+            Self::Json => {
+                let mut s = serde_json::to_string_pretty(value)?;
+                s.push('\n');
+                Ok(s)
+            }
+        }
+    }
+}
+
+impl FromStr for Format {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "toml" => Ok(Self::Toml),
+            "json" => Ok(Self::Json),
+            _ => Err(Error::UnknownFormat(s.to_string())),
+        }
+    }
+}
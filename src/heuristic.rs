@@ -0,0 +1,270 @@
+use crate::{problem::tolerance, solver::Stop, Problem, Solution};
+use std::{
+    cmp::Ordering,
+    sync::atomic::{self, AtomicUsize},
+    thread,
+};
+
+/// A selection built greedily and then improved by local search, for problems too large to be
+/// solved exactly.
+///
+/// The greedy pass takes as many copies as fit of each thing, by decreasing aggregated
+/// efficiency: its value per unit of cost, summed over the dimensions as fractions of each
+/// capacity. The local search then adds copies, drops a copy to refill the capacity greedily, or
+/// swaps a copy for copies of another thing, as long as any of these moves improves the value
+/// and it is not stopped.
+///
+/// The moves dropping a copy of each thing are tried by several threads, each from its own copy
+/// of the selection, and the move of the first thing that improves it is applied, as when they
+/// are tried in turn.
+#[derive(Debug, Clone)]
+pub(crate) struct Heuristic<'a> {
+    problem: &'a Problem,
+    /// Things by decreasing aggregated efficiency.
+    order: Vec<usize>,
+    remaining: Vec<usize>,
+    counts: Vec<usize>,
+    /// The number of members taken in each group.
+    taken: Vec<usize>,
+    value: f64,
+    stop: Stop,
+}
+
+impl<'a> Heuristic<'a> {
+    pub(crate) fn new(problem: &'a Problem, stop: Stop) -> Self {
+        let items = &problem.items;
+        let capacity = problem.costs.bounds();
+        let mut order = (0..items.len()).collect::<Vec<_>>();
+        order.sort_by(|&a, &b| {
+            problem
+                .efficiency(b)
+                .partial_cmp(&problem.efficiency(a))
+                .unwrap_or(Ordering::Equal)
+        });
+        Self {
+            problem,
+            order,
+            remaining: capacity.to_vec(),
+            counts: vec![0; items.len()],
+            taken: vec![0; problem.groups.len()],
+            value: 0.0,
+            stop,
+        }
+    }
+
+    /// The most copies of `thing` that can be added to the selection.
+    fn room(&self, thing: usize) -> usize {
+        let item = &self.problem.items[thing];
+        if let Some(g) = item.group {
+            if self.taken[g] > 0 && self.counts[thing] == 0 {
+                return 0;
+            }
+        }
+        let fit = item
+            .costs
+            .iter()
+            .zip(&self.remaining)
+            .filter(|(&c, _)| c > 0)
+            .map(|(c, r)| r / c)
+            .fold(usize::MAX, usize::min);
+        let available = match item.num {
+            Some(num) => num - self.counts[thing],
+            // Free things of unlimited copies are worth nothing, or the problem is unbounded.
+            None if fit == usize::MAX => 1 - self.counts[thing].min(1),
+            None => usize::MAX,
+        };
+        available.min(fit)
+    }
+
+    fn add(&mut self, thing: usize, k: usize) {
+        let item = &self.problem.items[thing];
+        if let Some(g) = item.group.filter(|_| self.counts[thing] == 0 && k > 0) {
+            self.taken[g] += 1;
+        }
+        for (r, c) in self.remaining.iter_mut().zip(&item.costs) {
+            *r -= c * k;
+        }
+        self.counts[thing] += k;
+        self.value += item.value * k as f64;
+    }
+
+    fn remove(&mut self, thing: usize, k: usize) {
+        let item = &self.problem.items[thing];
+        for (r, c) in self.remaining.iter_mut().zip(&item.costs) {
+            *r += c * k;
+        }
+        self.counts[thing] -= k;
+        self.value -= item.value * k as f64;
+        if let Some(g) = item.group.filter(|_| self.counts[thing] == 0 && k > 0) {
+            self.taken[g] -= 1;
+        }
+    }
+
+    /// Whether every group limited to exactly one member has one.
+    fn is_feasible(&self) -> bool {
+        self.problem
+            .groups
+            .iter()
+            .zip(&self.taken)
+            .all(|(g, t)| !g.exact || *t > 0)
+    }
+
+    /// Add as many copies as fit of the things worth something, except `except`, by decreasing
+    /// efficiency.
+    fn fill(&mut self, except: Option<usize>) {
+        for pos in 0..self.order.len() {
+            let idx = self.order[pos];
+            if Some(idx) != except && self.problem.items[idx].value > 0.0 {
+                let k = self.room(idx);
+                self.add(idx, k);
+            }
+        }
+    }
+
+    /// Take the most valuable member that fits in each group that needs one.
+    fn complete_groups(&mut self) {
+        let problem = self.problem;
+        for (g, group) in problem.groups.iter().enumerate() {
+            if !group.exact || self.taken[g] > 0 {
+                continue;
+            }
+            let best = group
+                .members
+                .iter()
+                .copied()
+                .filter(|&m| self.room(m) > 0)
+                .max_by(|&a, &b| {
+                    let (a, b) = (&problem.items[a], &problem.items[b]);
+                    a.value.partial_cmp(&b.value).unwrap_or(Ordering::Equal)
+                });
+            if let Some(m) = best {
+                self.add(m, 1);
+            }
+        }
+    }
+
+    /// Apply the first move that improves the value, over `jobs` threads, returning whether
+    /// there was one.
+    fn improve(&mut self, jobs: usize) -> bool {
+        for pos in 0..self.order.len() {
+            let idx = self.order[pos];
+            if self.problem.items[idx].value > 0.0 && self.room(idx) > 0 {
+                let k = self.room(idx);
+                self.add(idx, k);
+                return true;
+            }
+        }
+        if jobs <= 1 {
+            for i in 0..self.counts.len() {
+                if self.stop.is_due() {
+                    return false;
+                }
+                if self.drop_one(i) {
+                    return true;
+                }
+            }
+            return false;
+        }
+        // The first thing whose moves improve the selection, so that later ones are skipped.
+        let first = AtomicUsize::new(usize::MAX);
+        let found = thread::scope(|scope| {
+            let threads = (0..jobs)
+                .map(|t| {
+                    let mut search = self.clone();
+                    let first = &first;
+                    scope.spawn(move || {
+                        for i in (t..search.counts.len()).step_by(jobs) {
+                            if i > first.load(atomic::Ordering::Relaxed) || search.stop.is_due() {
+                                break;
+                            }
+                            if search.drop_one(i) {
+                                first.fetch_min(i, atomic::Ordering::Relaxed);
+                                return Some((i, search));
+                            }
+                        }
+                        None
+                    })
+                })
+                .collect::<Vec<_>>();
+            threads
+                .into_iter()
+                .filter_map(|thread| thread.join().expect("a local search thread panicked"))
+                .min_by_key(|(i, _)| *i)
+        });
+        match found {
+            Some((_, search)) => {
+                *self = search;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Apply the first move dropping a copy of thing `i` that improves the value, returning
+    /// whether there was one.
+    fn drop_one(&mut self, i: usize) -> bool {
+        let before = self.value;
+        let better = |value: f64| value > before + tolerance(before);
+        if self.counts[i] == 0 {
+            return false;
+        }
+        let counts = self.counts.clone();
+        self.remove(i, 1);
+        self.fill(Some(i));
+        if better(self.value) && self.is_feasible() {
+            return true;
+        }
+        self.restore(&counts);
+        for j in 0..self.counts.len() {
+            if j == i || self.problem.items[j].value <= 0.0 {
+                continue;
+            }
+            self.remove(i, 1);
+            let k = self.room(j);
+            self.add(j, k);
+            if k > 0 && better(self.value) && self.is_feasible() {
+                return true;
+            }
+            self.remove(j, k);
+            self.add(i, 1);
+        }
+        false
+    }
+
+    /// Go back to the selection of the given counts.
+    fn restore(&mut self, counts: &[usize]) {
+        for (idx, &num) in counts.iter().enumerate() {
+            if self.counts[idx] > num {
+                self.remove(idx, self.counts[idx] - num);
+            }
+        }
+        for (idx, &num) in counts.iter().enumerate() {
+            if self.counts[idx] < num {
+                self.add(idx, num - self.counts[idx]);
+            }
+        }
+    }
+
+    /// The selection found, improved by local search over `jobs` threads if `local`, `None` if
+    /// it does not satisfy the groups.
+    pub(crate) fn solve(mut self, local: bool, jobs: usize) -> Option<Solution> {
+        self.complete_groups();
+        self.fill(None);
+        if local {
+            while self.improve(jobs) {}
+        }
+        if !self.is_feasible() {
+            return None;
+        }
+        // Sum the value afresh rather than keep the rounding errors of the moves.
+        let items = &self.problem.items;
+        let value = items
+            .iter()
+            .zip(&self.counts)
+            .map(|(item, &num)| item.value * num as f64)
+            .sum();
+        let mut solution = Solution::new(self.problem, value, &self.counts);
+        solution.proven = false;
+        Some(solution)
+    }
+}
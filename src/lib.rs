@@ -0,0 +1,62 @@
+//! A solver for the multidimensional bounded knapsack problem.
+//!
+//! ```
+//! use mkp::{Problem, Thing};
+//!
+//! let problem = Problem::builder()
+//!     .costs(vec![10.0, 20.0])
+//!     .thing(Thing::new("A", 1.5, 3, vec![2.0, 1.0]))
+//!     .thing(Thing::new("B", 4.0, 2, vec![5.0, 4.0]))
+//!     .build()
+//!     .unwrap();
+//! let solution = mkp::solve(&problem).unwrap();
+//! assert_eq!(solution.value, 8.0);
+//! assert_eq!(solution.chosen["B"], 2);
+//! ```
+
+#[macro_use]
+extern crate log;
+
+mod bnb;
+mod costs;
+mod dimension;
+mod dp;
+mod error;
+mod format;
+mod heuristic;
+mod lp;
+mod pareto;
+mod problem;
+mod solution;
+mod solver;
+mod sparse;
+mod tie_break;
+mod verify;
+
+pub use dimension::{Amounts, Dimension};
+pub use error::{Error, Result, ValidationError};
+pub use format::Format;
+pub use pareto::solve_pareto_with;
+pub use problem::{
+    GroupLimit, Knapsack, Mode, Problem, ProblemBuilder, Relation, Rounding, Thing,
+    UncheckedProblem,
+};
+pub use solution::{Packing, Solution};
+pub use solver::{solve_optimal_with, solve_top_with, solve_with, Algorithm, Options};
+pub use tie_break::TieBreak;
+pub use verify::{Verification, Violation};
+
+/// Find an optimal solution of the problem with the default [`Options`].
+pub fn solve(problem: &Problem) -> Result<Solution> {
+    solve_with(problem, &Options::default())
+}
+
+/// The best value found by the dense DP with `jobs` threads, without tracing back the
+/// selection nor bounding it, `None` if no selection satisfies the groups.
+///
+/// Only built with the `bench` feature, for the benchmarks, which time the DP alone.
+#[cfg(feature = "bench")]
+#[doc(hidden)]
+pub fn dp_value(problem: &Problem, jobs: usize) -> Option<f64> {
+    dp::Dp::new(problem, 0, jobs).value()
+}
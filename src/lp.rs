@@ -0,0 +1,208 @@
+use crate::{Mode, Problem};
+
+/// Tolerance of the simplex on reduced costs and pivots.
+const EPS: f64 = 1e-9;
+
+/// The LP relaxation of a problem, solved by a bounded-variable simplex.
+///
+/// There is a row for each dimension and a column for each thing, plus a slack for each row, and
+/// an artificial for each row when covering. The bounds on the number of copies are kept out of
+/// the rows, so that the tableau only grows with the number of things.
+///
+/// Groups, relations and the split between knapsacks are relaxed away, so that the optimum is an
+/// upper bound of the value of any selection, or a lower bound when covering.
+#[derive(Debug)]
+struct Simplex {
+    /// The rows of the tableau, in terms of the current basis.
+    rows: Vec<Vec<f64>>,
+    /// The value of the basic variable of each row.
+    values: Vec<f64>,
+    basis: Vec<usize>,
+    upper: Vec<f64>,
+    /// Whether each non-basic variable is at its upper bound rather than at zero.
+    at_upper: Vec<bool>,
+}
+
+impl Simplex {
+    /// Maximise `objective`, returning `false` if it is unbounded.
+    fn maximise(&mut self, objective: &[f64]) -> bool {
+        loop {
+            let reduced = |j: usize| {
+                objective[j]
+                    - self
+                        .rows
+                        .iter()
+                        .zip(&self.basis)
+                        .map(|(row, &b)| objective[b] * row[j])
+                        .sum::<f64>()
+            };
+            // Bland's rule: the first improving variable enters, which prevents cycling.
+            let entering = (0..objective.len()).find_map(|j| {
+                if self.basis.contains(&j) || self.upper[j] <= 0.0 {
+                    return None;
+                }
+                let d = reduced(j);
+                if d > EPS && !self.at_upper[j] {
+                    Some((j, 1.0))
+                } else if d < -EPS && self.at_upper[j] {
+                    Some((j, -1.0))
+                } else {
+                    None
+                }
+            });
+            let (j, dir) = match entering {
+                Some(entering) => entering,
+                None => return true,
+            };
+            let mut step = self.upper[j];
+            let mut leaving = None;
+            for (i, row) in self.rows.iter().enumerate() {
+                let delta = -row[j] * dir;
+                let limit = if delta < -EPS {
+                    self.values[i] / -delta
+                } else if delta > EPS {
+                    (self.upper[self.basis[i]] - self.values[i]) / delta
+                } else {
+                    continue;
+                };
+                // Among ties, the first basic variable leaves, as Bland's rule requires.
+                let tie = |l: usize| limit <= step + EPS && self.basis[i] < self.basis[l];
+                if limit < step - EPS || leaving.is_some_and(tie) {
+                    step = limit.max(0.0);
+                    leaving = Some(i);
+                }
+            }
+            if step.is_infinite() {
+                return false;
+            }
+            for (i, row) in self.rows.iter().enumerate() {
+                self.values[i] -= row[j] * dir * step;
+            }
+            let r = match leaving {
+                Some(r) => r,
+                None => {
+                    // The entering variable reaches its other bound before any basic one.
+                    self.at_upper[j] = !self.at_upper[j];
+                    continue;
+                }
+            };
+            let left = self.basis[r];
+            self.at_upper[left] = -self.rows[r][j] * dir > 0.0;
+            self.values[r] = if self.at_upper[j] {
+                self.upper[j] - step
+            } else {
+                step
+            };
+            self.pivot(r, j);
+            self.at_upper[j] = false;
+        }
+    }
+
+    fn pivot(&mut self, r: usize, j: usize) {
+        let pivot = self.rows[r][j];
+        for x in self.rows[r].iter_mut() {
+            *x /= pivot;
+        }
+        let row = self.rows[r].clone();
+        for (i, other) in self.rows.iter_mut().enumerate() {
+            let factor = other[j];
+            if i != r && factor != 0.0 {
+                for (x, y) in other.iter_mut().zip(&row) {
+                    *x -= factor * y;
+                }
+            }
+        }
+        self.basis[r] = j;
+    }
+
+    /// The value of each variable.
+    fn solution(&self) -> Vec<f64> {
+        let mut x = self
+            .upper
+            .iter()
+            .zip(&self.at_upper)
+            .map(|(&u, &at_upper)| if at_upper { u } else { 0.0 })
+            .collect::<Vec<_>>();
+        for (&b, &v) in self.basis.iter().zip(&self.values) {
+            x[b] = v;
+        }
+        x
+    }
+}
+
+impl Problem {
+    /// The optimum of the LP relaxation of the problem, where fractions of things can be taken.
+    ///
+    /// It is an upper bound of the value of any selection, or a lower bound when covering, and
+    /// `None` if the relaxation is unbounded or, when covering, infeasible.
+    pub fn lp_bound(&self) -> Option<f64> {
+        let cover = self.mode == Mode::Cover;
+        let bounds = self.costs.bounds();
+        let (m, n) = (bounds.len(), self.items.len());
+        // The things, then a slack per row, then an artificial per row when covering.
+        let width = n + if cover { 2 * m } else { m };
+        let mut rows = vec![vec![0.0; width]; m];
+        for (d, row) in rows.iter_mut().enumerate() {
+            for (x, item) in row.iter_mut().zip(&self.items) {
+                *x = item.costs[d] as f64;
+            }
+            if cover {
+                row[n + d] = -1.0;
+                row[n + m + d] = 1.0;
+            } else {
+                row[n + d] = 1.0;
+            }
+        }
+        let mut upper = self
+            .items
+            .iter()
+            .map(|item| item.num.map_or(f64::INFINITY, |num| num as f64))
+            .collect::<Vec<_>>();
+        upper.resize(width, f64::INFINITY);
+        let mut simplex = Simplex {
+            rows,
+            values: bounds.iter().map(|&b| b as f64).collect(),
+            basis: (width - m..width).collect(),
+            upper,
+            at_upper: vec![false; width],
+        };
+        let sign = if cover { -1.0 } else { 1.0 };
+        let mut objective = self
+            .items
+            .iter()
+            .map(|item| sign * item.value)
+            .collect::<Vec<_>>();
+        objective.resize(width, 0.0);
+        if cover {
+            // Phase 1: drive the artificials out to find a covering, then keep them at zero.
+            let mut phase1 = vec![0.0; width];
+            phase1[n + m..].fill(-1.0);
+            simplex.maximise(&phase1);
+            if simplex
+                .values
+                .iter()
+                .zip(&simplex.basis)
+                .any(|(&v, &b)| b >= n + m && v > EPS)
+            {
+                return None;
+            }
+            simplex.upper[n + m..].fill(0.0);
+        }
+        if !simplex.maximise(&objective) {
+            return None;
+        }
+        let x = simplex.solution();
+        let relaxed = self
+            .items
+            .iter()
+            .zip(&x)
+            .map(|(item, x)| item.value * x)
+            .sum::<f64>();
+        let required = self
+            .items
+            .iter()
+            .map(|item| item.value * item.min as f64)
+            .sum::<f64>();
+        Some(relaxed + required)
+    }
+}
@@ -1,12 +1,14 @@
 use anyhow::Result;
 use clap_verbosity_flag::Verbosity;
-use serde::{Deserialize, Serialize};
+use mkp::{Algorithm, Format, Options, Solution, TieBreak, UncheckedProblem};
+use serde::Serialize;
 use simplelog::{ConfigBuilder, TermLogger, TerminalMode};
-use std::collections::BTreeMap;
 use std::{
     fs::File,
     io::{stdin, Read},
-    path::PathBuf,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicBool, Ordering},
+    time::{Duration, Instant},
 };
 use structopt::StructOpt;
 
@@ -20,183 +22,172 @@ struct Opt {
 
     #[structopt(long, short, parse(from_os_str))]
     input: Option<PathBuf>,
-}
 
-#[derive(Debug, Deserialize, Clone)]
-struct Thing {
-    name: String,
-    value: f64,
-    num: usize,
-    costs: Vec<usize>,
-}
+    /// Format of the input, guessed from the extension of `--input` if omitted.
+    #[structopt(long, possible_values = Format::VARIANTS, case_insensitive = true)]
+    input_format: Option<Format>,
 
-#[derive(Debug, Serialize)]
-struct TakedThing {
-    name: String,
-    num: usize,
-}
+    /// Format of the output.
+    #[structopt(long, possible_values = Format::VARIANTS, case_insensitive = true, default_value = "toml")]
+    output_format: Format,
 
-#[derive(Debug, Deserialize)]
-struct UncheckedProblem {
-    #[serde(alias = "Things")]
-    things: Vec<Thing>,
-    costs: Vec<usize>,
-}
+    /// Algorithm used to solve the problem.
+    #[structopt(long, possible_values = Algorithm::VARIANTS, case_insensitive = true, default_value = "auto")]
+    algorithm: Algorithm,
 
-impl UncheckedProblem {
-    fn check(self) -> Result<Problem> {
-        let len = self.costs.len();
-        if len == 0 {
-            anyhow::bail!("must contain at least one cost");
-        }
-        if self.things.iter().all(|thing| thing.costs.len() == len) {
-            Ok(Problem::new(self.things, self.costs))
-        } else {
-            anyhow::bail!("costs does not match");
-        }
-    }
-}
+    /// Memory budget of the DP table, or of the states of the sparse DP, in MiB, beyond which
+    /// `auto` switches to the sparse DP and then to branch-and-bound.
+    #[structopt(long, default_value = "1024")]
+    max_memory: usize,
 
-#[derive(Debug)]
-struct Costs(Vec<usize>);
+    /// Output the given number of best distinct selections instead of only the optimum.
+    #[structopt(long, conflicts_with = "all-optimal")]
+    top: Option<usize>,
 
-impl Costs {
-    fn end(&self) -> usize {
-        self.to_idx(&self.0)
-    }
+    /// Output every optimal selection instead of only one, up to `--max-solutions`.
+    #[structopt(long)]
+    all_optimal: bool,
 
-    fn iter(&self) -> std::ops::RangeInclusive<usize> {
-        0..=self.end()
-    }
+    /// Output the Pareto front of the objectives of the things, sweeping their weights in the
+    /// given number of steps.
+    #[structopt(long, conflicts_with_all = &["top", "all-optimal"])]
+    pareto: Option<usize>,
 
-    fn to_idx(&self, vec: &[usize]) -> usize {
-        let mut ans = 0;
-        for (idx, c) in self.0.iter().skip(1).enumerate() {
-            ans += vec[idx];
-            ans *= c + 1;
-        }
-        ans + *vec.last().unwrap() as usize
-    }
+    /// Maximum number of selections output by `--all-optimal`.
+    #[structopt(long, default_value = "100")]
+    max_solutions: usize,
 
-    fn to_cost(&self, mut c: usize) -> Vec<usize> {
-        let mut costs = Vec::new();
-        for bound in self.0.iter().rev() {
-            let idx = c % (bound + 1);
-            c /= bound + 1;
-            costs.push(idx);
-        }
-        costs.reverse();
-        costs
-    }
+    /// How to choose between selections of the same value.
+    #[structopt(long, possible_values = TieBreak::VARIANTS, case_insensitive = true)]
+    tie_break: Option<TieBreak>,
 
-    fn validate_sub(&self, bound: &[usize], cost: &[usize]) -> Option<usize> {
-        let mut ans = 0;
-        for idx in 0..bound.len() {
-            if cost[idx] > bound[idx] {
-                return None;
-            } else {
-                let c = if idx + 1 < self.0.len() {
-                    self.0[idx + 1]
-                } else {
-                    0
-                };
-                ans += bound[idx] - cost[idx];
-                ans *= c + 1;
-            }
-        }
-        Some(ans)
-    }
+    /// Seconds after which branch-and-bound and local search output the best selection found so
+    /// far, as Ctrl-C does, and the sparse DP gives up.
+    #[structopt(long)]
+    time_limit: Option<f64>,
+
+    /// Number of threads used by dynamic programming, branch-and-bound and local search, which
+    /// find the same selections whatever it is.
+    #[structopt(long, default_value = "1")]
+    jobs: usize,
+
+    #[structopt(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Debug, StructOpt)]
+enum Command {
+    /// Check a solution against a problem instead of solving it.
+    ///
+    /// Exits with the sum of the kinds of violations found: 2 for unknown things or knapsacks, 4
+    /// for violated counts, groups, assignments, conflicts or requirements, 8 for exceeded
+    /// capacities or uncovered costs and 16 for a wrong value.
+    Check {
+        /// The problem, in the format of `--input-format` or guessed from its extension.
+        #[structopt(long, parse(from_os_str))]
+        problem: PathBuf,
+
+        /// The solution, in the format of `--input-format` or guessed from its extension.
+        #[structopt(long, parse(from_os_str))]
+        solution: PathBuf,
+    },
 }
 
-#[derive(Debug)]
-struct Problem {
-    things: Vec<Thing>,
-    costs: Costs,
-    dp: Vec<f64>,
+impl Opt {
+    fn input_format(&self, path: Option<&Path>) -> Format {
+        self.input_format
+            .or_else(|| path.and_then(Format::from_path))
+            .unwrap_or_default()
+    }
 }
 
-impl Problem {
-    fn new(things: Vec<Thing>, costs: Vec<usize>) -> Self {
-        let costs = Costs(costs);
-        let dp = vec![0.0; costs.end() + 1];
+/// Set by the first Ctrl-C, which stops the search gracefully.
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
 
-        Self { things, costs, dp }
+extern "C" fn interrupt(_: libc::c_int) {
+    INTERRUPTED.store(true, Ordering::Relaxed);
+    // A second Ctrl-C kills the process, e.g. while solving with an algorithm that cannot stop.
+    unsafe {
+        libc::signal(libc::SIGINT, libc::SIG_DFL);
     }
+}
 
-    fn zero_one_pack(&mut self, cost: &[usize], value: f64, k: usize, taked: &mut Vec<usize>) {
-        for c in self.costs.iter().rev() {
-            let bound = self.costs.to_cost(c);
-            if let Some(idx) = self.costs.validate_sub(&bound, cost) {
-                let v = self.dp[idx] + value;
-                if v > self.dp[c] {
-                    self.dp[c] = v;
-                    taked[c] = taked[idx] + k;
-                }
+/// Several solutions, by decreasing value or along the Pareto front.
+#[derive(Debug, Serialize)]
+struct Ranking {
+    solutions: Vec<Solution>,
+}
+
+fn read(path: Option<&Path>) -> Result<String> {
+    let mut buf = String::new();
+    if let Some(path) = path {
+        let mut input_file = File::open(path)?;
+        input_file.read_to_string(&mut buf)?;
+    } else {
+        let mut stdin = stdin();
+        while let Ok(n_bytes) = stdin.read_to_string(&mut buf) {
+            if n_bytes == 0 {
+                break;
             }
         }
     }
+    Ok(buf)
+}
 
-    fn multi_pack(&mut self, cost: &[usize], value: f64, mut num: usize) -> Vec<usize> {
-        let mut k = 1;
-        let mut taked = vec![0; self.costs.end() + 1];
-        while k < num {
-            self.zero_one_pack(
-                &cost.iter().map(|c| c * k as usize).collect::<Vec<_>>(),
-                k as f64 * value,
-                k,
-                &mut taked,
-            );
-            num -= k;
-            k *= 2;
-        }
-        if num > 0 {
-            let k = num;
-            self.zero_one_pack(
-                &cost.iter().map(|c| c * k as usize).collect::<Vec<_>>(),
-                k as f64 * value,
-                k,
-                &mut taked,
-            );
-        }
-
-        taked
+fn solve(opt: &Opt) -> Result<()> {
+    let input = opt.input.as_deref();
+    let problem = opt
+        .input_format(input)
+        .deserialize::<UncheckedProblem>(&read(input)?)?
+        .check()?;
+    let options = Options {
+        algorithm: opt.algorithm,
+        max_memory: opt.max_memory.saturating_mul(1 << 20),
+        tie_break: opt.tie_break,
+        deadline: opt
+            .time_limit
+            .map(Duration::try_from_secs_f64)
+            .transpose()?
+            .and_then(|limit| Instant::now().checked_add(limit)),
+        interrupt: Some(&INTERRUPTED),
+        jobs: opt.jobs,
+    };
+    unsafe {
+        libc::signal(
+            libc::SIGINT,
+            interrupt as extern "C" fn(libc::c_int) as libc::sighandler_t,
+        );
     }
-
-    fn solve(mut self) -> Solution {
-        let mut taked = Vec::new();
-        let mut chosen = Vec::new();
-        for thing in self.things.clone() {
-            taked.push(self.multi_pack(&thing.costs, thing.value, thing.num));
-        }
-        let mut v = self.costs.end();
-        for k in (0..self.things.len()).rev() {
-            let num = taked[k][v];
-            chosen.push(num);
-            v -= self.costs.to_idx(
-                &self.things[k]
-                    .costs
-                    .iter()
-                    .map(|c| *c * num as usize)
-                    .collect::<Vec<_>>(),
-            );
-        }
-        chosen.reverse();
-        let chosen = chosen
-            .iter()
-            .enumerate()
-            .map(|(idx, num)| (self.things[idx].name.clone(), *num))
-            .collect();
-        Solution {
-            value: self.dp[self.costs.end()],
-            chosen,
-        }
+    if let Some(k) = opt.top {
+        let solutions = mkp::solve_top_with(&problem, k, &options)?;
+        print!("{}", opt.output_format.serialize(&Ranking { solutions })?);
+    } else if let Some(steps) = opt.pareto {
+        let solutions = mkp::solve_pareto_with(&problem, steps, &options)?;
+        print!("{}", opt.output_format.serialize(&Ranking { solutions })?);
+    } else if opt.all_optimal {
+        let solutions = mkp::solve_optimal_with(&problem, opt.max_solutions, &options)?;
+        print!("{}", opt.output_format.serialize(&Ranking { solutions })?);
+    } else {
+        let solution = mkp::solve_with(&problem, &options)?;
+        print!("{}", opt.output_format.serialize(&solution)?);
     }
+    Ok(())
 }
 
-#[derive(Debug, Serialize)]
-struct Solution {
-    value: f64,
-    chosen: BTreeMap<String, usize>,
+fn check(opt: &Opt, problem: &Path, solution: &Path) -> Result<i32> {
+    let problem = opt
+        .input_format(Some(problem))
+        .deserialize::<UncheckedProblem>(&read(Some(problem))?)?
+        .check()?;
+    let solution = opt
+        .input_format(Some(solution))
+        .deserialize::<Solution>(&read(Some(solution))?)?;
+    let verification = problem.verify(&solution);
+    for violation in verification.violations.iter() {
+        warn!("{:?}", violation);
+    }
+    print!("{}", opt.output_format.serialize(&verification)?);
+    Ok(verification.code())
 }
 
 fn main() -> Result<()> {
@@ -206,20 +197,14 @@ fn main() -> Result<()> {
         TermLogger::init(level.to_level_filter(), log_config, TerminalMode::Mixed)?;
     }
     debug!("opt={:?}", opt);
-    let mut buf = String::new();
-    if let Some(path) = &opt.input {
-        let mut input_file = File::open(path)?;
-        input_file.read_to_string(&mut buf)?;
-    } else {
-        let mut stdin = stdin();
-        while let Ok(n_bytes) = stdin.read_to_string(&mut buf) {
-            if n_bytes == 0 {
-                break;
+    match &opt.command {
+        None => solve(&opt),
+        Some(Command::Check { problem, solution }) => {
+            let code = check(&opt, problem, solution)?;
+            if code != 0 {
+                std::process::exit(code);
             }
+            Ok(())
         }
     }
-
-    let solution = toml::from_str::<UncheckedProblem>(&buf)?.check()?.solve();
-    print!("{}", toml::to_string(&solution)?);
-    Ok(())
 }
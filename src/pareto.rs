@@ -0,0 +1,93 @@
+use crate::{problem::tolerance, solve_with, Error, Mode, Options, Problem, Result, Solution};
+
+/// The share of the weight every objective keeps, so that no objective is ignored and the
+/// selections found are not dominated by selections of equal weighted value.
+const MIN_WEIGHT: f64 = 1e-4;
+
+/// Find the Pareto front of the objectives of the problem by a weighted-sum sweep, the weights
+/// of the objectives moving by `1 / steps` of the total from one weighting to the next.
+///
+/// Every weighting is solved with the given options, the objectives being summed into the value
+/// of each thing. Only the points of the front optimal for some weighting are found, in the
+/// order of the sweep, from the weighting favouring the first objective to the last one. The
+/// `value` of each solution is the value of its things, with the bound of the problem, and
+/// `objectives` their totals.
+pub fn solve_pareto_with(
+    problem: &Problem,
+    steps: usize,
+    options: &Options,
+) -> Result<Vec<Solution>> {
+    if problem.objectives.is_empty() {
+        return Err(Error::NoObjectives);
+    }
+    let steps = steps.max(1);
+    let bound = problem.lp_bound();
+    let mut front = Vec::<Solution>::new();
+    for parts in weightings(problem.objectives.len(), steps) {
+        let weights = parts
+            .iter()
+            .map(|&p| (p as f64 + MIN_WEIGHT) / steps as f64)
+            .collect::<Vec<_>>();
+        info!("solving with weights {:?}", weights);
+        let mut solution = solve_with(&problem.weighted(&weights), options)?;
+        solution.value = problem
+            .things
+            .iter()
+            .map(|thing| thing.value * solution.chosen[&thing.name] as f64)
+            .sum();
+        let solution = solution.with_bound(bound);
+        if front.iter().any(|point| problem.covers(point, &solution)) {
+            continue;
+        }
+        front.retain(|point| !problem.covers(&solution, point));
+        front.push(solution);
+    }
+    Ok(front)
+}
+
+impl Problem {
+    /// The same problem with the value of each thing replaced by the weighted sum of its
+    /// objectives.
+    fn weighted(&self, weights: &[f64]) -> Problem {
+        let mut problem = self.clone();
+        for (item, thing) in problem.items.iter_mut().zip(&self.things) {
+            item.value = self
+                .objectives
+                .iter()
+                .zip(weights)
+                .filter_map(|(objective, w)| Some(thing.objectives.get(objective)? * w))
+                .sum();
+        }
+        problem
+    }
+
+    /// Whether the objectives of `a` are all at least as good as those of `b`.
+    fn covers(&self, a: &Solution, b: &Solution) -> bool {
+        self.objectives.iter().all(|objective| {
+            let (a, b) = (a.objectives[objective], b.objectives[objective]);
+            match self.mode {
+                Mode::Pack => a >= b - tolerance(b),
+                Mode::Cover => a <= b + tolerance(b),
+            }
+        })
+    }
+}
+
+/// Every way of splitting `steps` parts of weight between `len` objectives, the first
+/// objective getting the most parts first.
+fn weightings(len: usize, steps: usize) -> Vec<Vec<usize>> {
+    if len == 1 {
+        return vec![vec![steps]];
+    }
+    (0..=steps)
+        .rev()
+        .flat_map(|first| {
+            weightings(len - 1, steps - first)
+                .into_iter()
+                .map(move |mut rest| {
+                    rest.insert(0, first);
+                    rest
+                })
+        })
+        .collect()
+}
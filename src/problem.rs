@@ -0,0 +1,930 @@
+use crate::{costs::Costs, Amounts, Dimension, Error, Result, ValidationError};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// A kind of thing that can be packed.
+#[derive(Debug, Deserialize, Clone)]
+pub struct Thing {
+    pub name: String,
+    pub value: f64,
+    /// The number of copies available, unlimited if `None`.
+    ///
+    /// Given as an integer, or omitted or `"inf"` for unlimited copies.
+    #[serde(default, deserialize_with = "deserialize_num")]
+    pub num: Option<usize>,
+    /// The number of copies that must be taken.
+    #[serde(default)]
+    pub min: usize,
+    /// The group of mutually exclusive things this thing belongs to.
+    #[serde(default)]
+    pub group: Option<String>,
+    /// The names of the things that cannot be taken together with this thing.
+    #[serde(default)]
+    pub conflicts: Vec<String>,
+    /// The names of the things that must be taken when this thing is taken.
+    #[serde(default)]
+    pub requires: Vec<String>,
+    /// The cost of each dimension, `0` for the dimensions not named.
+    pub costs: Amounts,
+    /// The value of each named objective, `0` for the objectives not listed.
+    ///
+    /// Only used when computing the Pareto front of the objectives.
+    #[serde(default)]
+    pub objectives: BTreeMap<String, f64>,
+}
+
+impl Thing {
+    /// Create a thing with at most `num` copies, each worth `value` and consuming `costs`.
+    pub fn new(name: impl Into<String>, value: f64, num: usize, costs: impl Into<Amounts>) -> Self {
+        Self {
+            name: name.into(),
+            value,
+            num: Some(num),
+            min: 0,
+            group: None,
+            conflicts: Vec::new(),
+            requires: Vec::new(),
+            costs: costs.into(),
+            objectives: BTreeMap::new(),
+        }
+    }
+
+    /// Create a thing with unlimited copies, each worth `value` and consuming `costs`.
+    pub fn unbounded(name: impl Into<String>, value: f64, costs: impl Into<Amounts>) -> Self {
+        Self {
+            name: name.into(),
+            value,
+            num: None,
+            min: 0,
+            group: None,
+            conflicts: Vec::new(),
+            requires: Vec::new(),
+            costs: costs.into(),
+            objectives: BTreeMap::new(),
+        }
+    }
+
+    /// Require at least `min` copies to be taken.
+    pub fn with_min(mut self, min: usize) -> Self {
+        self.min = min;
+        self
+    }
+
+    /// Put the thing in a group of mutually exclusive things.
+    pub fn with_group(mut self, group: impl Into<String>) -> Self {
+        self.group = Some(group.into());
+        self
+    }
+
+    /// Forbid taking the thing together with the thing named `other`.
+    pub fn conflicts_with(mut self, other: impl Into<String>) -> Self {
+        self.conflicts.push(other.into());
+        self
+    }
+
+    /// Require the thing named `other` to be taken when the thing is taken.
+    pub fn requires(mut self, other: impl Into<String>) -> Self {
+        self.requires.push(other.into());
+        self
+    }
+
+    /// Set the value of the thing for a named objective.
+    pub fn with_objective(mut self, objective: impl Into<String>, value: f64) -> Self {
+        self.objectives.insert(objective.into(), value);
+        self
+    }
+}
+
+/// A knapsack of its own capacity, for problems packing things into several knapsacks.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Knapsack {
+    pub name: String,
+    /// The capacity of each dimension, `0` for the dimensions not named.
+    pub costs: Amounts,
+}
+
+impl Knapsack {
+    /// Create a knapsack of the given capacity.
+    pub fn new(name: impl Into<String>, costs: impl Into<Amounts>) -> Self {
+        Self {
+            name: name.into(),
+            costs: costs.into(),
+        }
+    }
+}
+
+/// What the costs of a problem bound and how its value is optimised.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Mode {
+    /// The costs are capacities not to exceed, and the value is maximised.
+    #[default]
+    Pack,
+    /// The costs are requirements to meet, and the value is minimised, e.g. the price of
+    /// meeting nutritional targets.
+    Cover,
+}
+
+/// How many members of a group can be taken.
+///
+/// A member is taken when at least one copy of it is chosen, in any quantity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum GroupLimit {
+    AtMostOne,
+    ExactlyOne,
+}
+
+fn deserialize_num<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<Option<usize>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Num {
+        Finite(usize),
+        Named(String),
+    }
+
+    match Num::deserialize(deserializer)? {
+        Num::Finite(num) => Ok(Some(num)),
+        Num::Named(name) if name == "inf" => Ok(None),
+        Num::Named(name) => Err(serde::de::Error::custom(format!(
+            "invalid num `{}`, expected an integer or \"inf\"",
+            name
+        ))),
+    }
+}
+
+/// A problem as read from the input, not yet validated.
+#[derive(Debug, Default, Deserialize)]
+pub struct UncheckedProblem {
+    #[serde(alias = "Things")]
+    pub things: Vec<Thing>,
+    /// The capacity of each dimension, when packing into a single knapsack, or its requirement
+    /// when covering.
+    ///
+    /// Given as a list, or as a table by dimension name to name the dimensions of the problem.
+    /// The costs of the things and the capacities of the knapsacks are then given by name too.
+    #[serde(default)]
+    pub costs: Amounts,
+    /// Whether to pack things under the capacities or to cover the requirements.
+    #[serde(default)]
+    pub mode: Mode,
+    /// The number of integral units per unit of each dimension, `1` for all of them if omitted.
+    ///
+    /// Costs are solved in integral units: capacities are rounded down and the costs of things
+    /// are rounded up, so that solutions stay feasible.
+    #[serde(default)]
+    pub scale: Option<Amounts>,
+    /// The limit of each group by name, [`GroupLimit::AtMostOne`] for groups not listed.
+    #[serde(default)]
+    pub groups: BTreeMap<String, GroupLimit>,
+    /// The knapsacks to pack the things into, in place of `costs`.
+    #[serde(default)]
+    pub knapsacks: Vec<Knapsack>,
+}
+
+impl UncheckedProblem {
+    /// Validate the problem, reporting every problem found.
+    pub fn check(self) -> Result<Problem> {
+        let errors = self.validate();
+        if !errors.is_empty() {
+            return Err(Error::Invalid(errors));
+        }
+        let names = self.names();
+        let len = self.dimensions(&names);
+        let scale = self
+            .scale
+            .map_or_else(|| vec![1.0; len], |scale| scale.resolve(&names, 1.0));
+        let resolve = |amounts: Amounts| Amounts::List(amounts.resolve(&names, 0.0));
+        let things = self
+            .things
+            .into_iter()
+            .map(|thing| Thing {
+                costs: resolve(thing.costs),
+                ..thing
+            })
+            .collect();
+        let knapsacks = self
+            .knapsacks
+            .into_iter()
+            .map(|knapsack| Knapsack {
+                costs: resolve(knapsack.costs),
+                ..knapsack
+            })
+            .collect();
+        Problem::new(
+            things,
+            self.costs.resolve(&names, 0.0),
+            knapsacks,
+            self.mode,
+            &scale,
+            &self.groups,
+            names,
+        )
+    }
+
+    /// The names of the dimensions, sorted, given by the capacity or the knapsacks, or none if
+    /// the dimensions are not named.
+    fn names(&self) -> Vec<String> {
+        let knapsacks = self.knapsacks.iter().map(|knapsack| &knapsack.costs);
+        std::iter::once(&self.costs)
+            .chain(knapsacks)
+            .filter_map(|amounts| match amounts {
+                Amounts::Named(named) => Some(named.keys().cloned()),
+                Amounts::List(_) => None,
+            })
+            .flatten()
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect()
+    }
+
+    /// The number of dimensions, given by their names, the capacity or the first knapsack.
+    fn dimensions(&self, names: &[String]) -> usize {
+        if !names.is_empty() {
+            return names.len();
+        }
+        match self.knapsacks.first() {
+            Some(knapsack) if self.costs.is_empty() => knapsack.costs.entries().len(),
+            _ => self.costs.entries().len(),
+        }
+    }
+
+    /// Check that the amounts of `owner` are named if and only if the dimensions are, after
+    /// dimensions of the problem, and return the length of the amounts if given as a list.
+    fn check_names(
+        names: &[String],
+        owner: impl Fn() -> String,
+        amounts: &Amounts,
+        errors: &mut Vec<ValidationError>,
+    ) -> Option<usize> {
+        match amounts {
+            Amounts::List(list) if names.is_empty() => return Some(list.len()),
+            Amounts::List(list) if !list.is_empty() => {
+                errors.push(ValidationError::PositionalCosts { owner: owner() })
+            }
+            Amounts::List(_) => {}
+            Amounts::Named(named) => {
+                for dimension in named.keys().filter(|&key| !names.contains(key)) {
+                    errors.push(ValidationError::UnknownDimension {
+                        owner: owner(),
+                        dimension: dimension.clone(),
+                    });
+                }
+            }
+        }
+        None
+    }
+
+    fn validate(&self) -> Vec<ValidationError> {
+        let mut errors = Vec::new();
+        let dimensions = self.names();
+        let len = self.dimensions(&dimensions);
+        if len == 0 {
+            errors.push(ValidationError::NoCosts);
+        }
+        Self::check_names(
+            &dimensions,
+            || "capacity".to_string(),
+            &self.costs,
+            &mut errors,
+        );
+        if !self.costs.is_empty() && !self.knapsacks.is_empty() {
+            errors.push(ValidationError::CostsAndKnapsacks);
+        }
+        if self.mode == Mode::Cover && !self.knapsacks.is_empty() {
+            errors.push(ValidationError::CoverKnapsacks);
+        }
+        let mut knapsacks = HashMap::new();
+        for (index, knapsack) in self.knapsacks.iter().enumerate() {
+            let name = || knapsack.name.clone();
+            if let Some(&first) = knapsacks.get(knapsack.name.as_str()) {
+                errors.push(ValidationError::DuplicateKnapsack {
+                    index,
+                    name: name(),
+                    first,
+                });
+            } else {
+                knapsacks.insert(knapsack.name.as_str(), index);
+            }
+            let owner = || format!("knapsack #{} `{}`", index, knapsack.name);
+            match Self::check_names(&dimensions, owner, &knapsack.costs, &mut errors) {
+                Some(actual) if actual != len => {
+                    errors.push(ValidationError::KnapsackCostsMismatch {
+                        index,
+                        name: name(),
+                        expected: len,
+                        actual,
+                    })
+                }
+                _ => {}
+            }
+            for (dimension, capacity) in knapsack.costs.entries() {
+                if !(capacity.is_finite() && capacity >= 0.0) {
+                    errors.push(ValidationError::InvalidKnapsackCapacity {
+                        index,
+                        name: name(),
+                        dimension,
+                        capacity,
+                    });
+                }
+            }
+        }
+        for (dimension, capacity) in self.costs.entries() {
+            if !(capacity.is_finite() && capacity >= 0.0) {
+                errors.push(ValidationError::InvalidCapacity {
+                    dimension,
+                    capacity,
+                });
+            } else if capacity == 0.0 && self.mode == Mode::Pack {
+                errors.push(ValidationError::ZeroCapacity { dimension });
+            }
+        }
+        if let Some(scale) = &self.scale {
+            let owner = || "scale".to_string();
+            match Self::check_names(&dimensions, owner, scale, &mut errors) {
+                Some(actual) if actual != len => errors.push(ValidationError::ScaleMismatch {
+                    expected: len,
+                    actual,
+                }),
+                _ => {}
+            }
+            for (dimension, scale) in scale.entries() {
+                if !(scale.is_finite() && scale > 0.0) {
+                    errors.push(ValidationError::InvalidScale { dimension, scale });
+                }
+            }
+        }
+        let mut names = HashMap::new();
+        for (index, thing) in self.things.iter().enumerate() {
+            let name = || thing.name.clone();
+            if let Some(&first) = names.get(thing.name.as_str()) {
+                errors.push(ValidationError::DuplicateName {
+                    index,
+                    name: name(),
+                    first,
+                });
+            } else {
+                names.insert(thing.name.as_str(), index);
+            }
+            if !(thing.value.is_finite() && thing.value >= 0.0) {
+                errors.push(ValidationError::InvalidValue {
+                    index,
+                    name: name(),
+                    value: thing.value,
+                });
+            }
+            for (objective, &value) in thing.objectives.iter() {
+                if !(value.is_finite() && value >= 0.0) {
+                    errors.push(ValidationError::InvalidObjective {
+                        index,
+                        name: name(),
+                        objective: objective.clone(),
+                        value,
+                    });
+                }
+            }
+            let owner = || format!("thing #{} `{}`", index, thing.name);
+            match Self::check_names(&dimensions, owner, &thing.costs, &mut errors) {
+                Some(actual) if actual != len => errors.push(ValidationError::CostsMismatch {
+                    index,
+                    name: name(),
+                    expected: len,
+                    actual,
+                }),
+                _ => {}
+            }
+            let costs = thing.costs.entries();
+            for &(ref dimension, cost) in costs.iter() {
+                if !(cost.is_finite() && cost >= 0.0) {
+                    errors.push(ValidationError::InvalidCost {
+                        index,
+                        name: name(),
+                        dimension: dimension.clone(),
+                        cost,
+                    });
+                }
+            }
+            let free = costs.iter().all(|(_, c)| *c == 0.0);
+            let worth = thing.value > 0.0 || thing.objectives.values().any(|v| *v > 0.0);
+            if thing.num.is_none() && worth && free && self.mode == Mode::Pack {
+                errors.push(ValidationError::Unbounded {
+                    index,
+                    name: name(),
+                });
+            }
+            if let Some(num) = thing.num.filter(|num| thing.min > *num) {
+                errors.push(ValidationError::MinExceedsNum {
+                    index,
+                    name: name(),
+                    min: thing.min,
+                    num,
+                });
+            }
+        }
+        for (group, limit) in self.groups.iter() {
+            let members = || {
+                self.things
+                    .iter()
+                    .filter(move |thing| thing.group.as_ref() == Some(group))
+            };
+            if *limit == GroupLimit::ExactlyOne && members().next().is_none() {
+                errors.push(ValidationError::EmptyGroup {
+                    group: group.clone(),
+                });
+            }
+        }
+        let mut required = BTreeMap::<_, Vec<_>>::new();
+        for thing in self.things.iter().filter(|thing| thing.min > 0) {
+            if let Some(group) = &thing.group {
+                required.entry(group).or_default().push(thing.name.clone());
+            }
+        }
+        for (group, things) in required.into_iter().filter(|(_, t)| t.len() > 1) {
+            errors.push(ValidationError::GroupMinimums {
+                group: group.clone(),
+                things,
+            });
+        }
+        for (index, thing) in self.things.iter().enumerate() {
+            let relations = thing.conflicts.iter().map(|r| (Relation::Conflicts, r));
+            let relations = relations.chain(thing.requires.iter().map(|r| (Relation::Requires, r)));
+            for (relation, reference) in relations {
+                if !names.contains_key(reference.as_str()) {
+                    errors.push(ValidationError::UnknownReference {
+                        index,
+                        name: thing.name.clone(),
+                        relation,
+                        reference: reference.clone(),
+                    });
+                }
+            }
+        }
+        errors.extend(self.requirement_cycles(&names));
+        errors
+    }
+
+    /// Find the cycles of requirements, each reported once.
+    fn requirement_cycles(&self, names: &HashMap<&str, usize>) -> Vec<ValidationError> {
+        fn visit(
+            problem: &UncheckedProblem,
+            names: &HashMap<&str, usize>,
+            idx: usize,
+            state: &mut [u8],
+            path: &mut Vec<usize>,
+            errors: &mut Vec<ValidationError>,
+        ) {
+            state[idx] = 1;
+            path.push(idx);
+            for reference in problem.things[idx].requires.iter() {
+                let next = match names.get(reference.as_str()) {
+                    Some(&next) => next,
+                    None => continue,
+                };
+                match state[next] {
+                    0 => visit(problem, names, next, state, path, errors),
+                    1 => {
+                        let start = path.iter().position(|&p| p == next).unwrap();
+                        errors.push(ValidationError::RequirementCycle {
+                            things: path[start..]
+                                .iter()
+                                .map(|&p| problem.things[p].name.clone())
+                                .collect(),
+                        });
+                    }
+                    _ => {}
+                }
+            }
+            path.pop();
+            state[idx] = 2;
+        }
+
+        let mut errors = Vec::new();
+        let mut state = vec![0; self.things.len()];
+        for idx in 0..self.things.len() {
+            if state[idx] == 0 {
+                visit(self, names, idx, &mut state, &mut Vec::new(), &mut errors);
+            }
+        }
+        errors
+    }
+}
+
+/// A relation between two things.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Relation {
+    /// The things cannot be taken together.
+    Conflicts,
+    /// The second thing must be taken when the first one is.
+    Requires,
+}
+
+impl std::fmt::Display for Relation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Conflicts => f.write_str("conflicts"),
+            Self::Requires => f.write_str("requires"),
+        }
+    }
+}
+
+/// A cost that is not a whole number of units and has been rounded.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Rounding {
+    /// The thing whose cost is rounded, or `None` for a capacity.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thing: Option<String>,
+    /// The knapsack whose capacity is rounded, or `None` for the capacity of the problem or the
+    /// cost of a thing.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub knapsack: Option<String>,
+    /// The dimension of the cost.
+    pub dimension: Dimension,
+    /// The cost as given.
+    pub original: f64,
+    /// The cost as solved.
+    pub rounded: f64,
+}
+
+/// A thing as seen by the solvers, with its costs in integral units.
+///
+/// The minimum quantities are taken out of the problem: `num` only counts the copies taken
+/// beyond `min`, and the capacity of the problem is what remains after the `min` copies.
+#[derive(Debug, Clone)]
+pub(crate) struct Item {
+    pub(crate) value: f64,
+    /// The number of optional copies available, unlimited if `None`.
+    pub(crate) num: Option<usize>,
+    pub(crate) min: usize,
+    pub(crate) costs: Vec<usize>,
+    /// The index of the group of the thing.
+    pub(crate) group: Option<usize>,
+    /// The indices of the things that cannot be taken together with this one, both ways.
+    pub(crate) conflicts: Vec<usize>,
+    /// The indices of the things that must be taken when this one is.
+    pub(crate) requires: Vec<usize>,
+    /// The indices of the things that require this one.
+    pub(crate) required_by: Vec<usize>,
+}
+
+/// A group of mutually exclusive things.
+#[derive(Debug, Clone)]
+pub(crate) struct Group {
+    pub(crate) name: String,
+    pub(crate) limit: GroupLimit,
+    /// The indices of the things in the group.
+    pub(crate) members: Vec<usize>,
+    /// Whether the solvers must take one member, i.e. the group is limited to exactly one and
+    /// none of its members is already taken by its minimum quantity.
+    pub(crate) exact: bool,
+}
+
+/// A validated problem, ready to be solved.
+#[derive(Debug, Clone)]
+pub struct Problem {
+    pub(crate) things: Vec<Thing>,
+    /// The capacity of each dimension, summed over the knapsacks if any.
+    pub(crate) capacity: Vec<f64>,
+    pub(crate) knapsacks: Vec<Knapsack>,
+    pub(crate) mode: Mode,
+    /// The capacity of each knapsack in integral units, minimum quantities included.
+    pub(crate) bins: Vec<Vec<usize>>,
+    pub(crate) rounding: Vec<Rounding>,
+    pub(crate) items: Vec<Item>,
+    pub(crate) groups: Vec<Group>,
+    /// The capacity in integral units left after taking the minimum quantities, or the
+    /// requirement still to be met when covering.
+    pub(crate) costs: Costs,
+    /// The indices of the things sorted by name.
+    pub(crate) by_name: Vec<usize>,
+    /// The names of the objectives of any thing, sorted.
+    pub(crate) objectives: Vec<String>,
+    /// The names of the dimensions, sorted, or none if they are not named.
+    pub(crate) dimensions: Vec<String>,
+}
+
+impl Problem {
+    fn new(
+        things: Vec<Thing>,
+        capacity: Vec<f64>,
+        knapsacks: Vec<Knapsack>,
+        mode: Mode,
+        scale: &[f64],
+        limits: &BTreeMap<String, GroupLimit>,
+        dimensions: Vec<String>,
+    ) -> Result<Self> {
+        // Capacities are rounded down and costs up so that solutions stay feasible, the other way
+        // round for requirements.
+        let cover = mode == Mode::Cover;
+        let mut rounding = Vec::new();
+        let mut overflow = Vec::new();
+        let mut discretize = |thing: Option<&str>,
+                              knapsack: Option<&str>,
+                              dimension: usize,
+                              original: f64,
+                              up: bool| {
+            let units = original * scale[dimension];
+            let nearest = units.round();
+            let units = if (units - nearest).abs() <= tolerance(units) {
+                nearest
+            } else {
+                let rounded = if up { units.ceil() } else { units.floor() };
+                rounding.push(Rounding {
+                    thing: thing.map(str::to_string),
+                    knapsack: knapsack.map(str::to_string),
+                    dimension: Dimension::of(dimension, &dimensions),
+                    original,
+                    rounded: rounded / scale[dimension],
+                });
+                rounded
+            };
+            if units >= usize::MAX as f64 {
+                let owner = match (thing, knapsack) {
+                    (Some(thing), _) => {
+                        let index = things.iter().position(|t| t.name == thing).unwrap();
+                        format!("thing #{} `{}`", index, thing)
+                    }
+                    (_, Some(knapsack)) => {
+                        let index = knapsacks.iter().position(|k| k.name == knapsack).unwrap();
+                        format!("knapsack #{} `{}`", index, knapsack)
+                    }
+                    _ => "capacity".to_string(),
+                };
+                overflow.push(ValidationError::AmountOverflow {
+                    owner,
+                    dimension: Dimension::of(dimension, &dimensions),
+                    amount: original,
+                });
+                return 0;
+            }
+            units as usize
+        };
+        let bins = knapsacks
+            .iter()
+            .map(|knapsack| {
+                knapsack
+                    .costs
+                    .list()
+                    .iter()
+                    .enumerate()
+                    .map(|(d, &c)| discretize(None, Some(&knapsack.name), d, c, false))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let (capacity, mut costs) = if knapsacks.is_empty() {
+            let costs = capacity
+                .iter()
+                .enumerate()
+                .map(|(d, &c)| discretize(None, None, d, c, cover))
+                .collect::<Vec<_>>();
+            (capacity, costs)
+        } else {
+            let total = |d: usize| knapsacks.iter().map(|k| k.costs.list()[d]).sum::<f64>();
+            let units = |d: usize| bins.iter().map(|b| b[d]).fold(0, usize::saturating_add);
+            (0..scale.len()).map(|d| (total(d), units(d))).unzip()
+        };
+        let mut groups = things
+            .iter()
+            .filter_map(|thing| Some((thing.group.as_deref()?, GroupLimit::AtMostOne)))
+            .chain(limits.iter().map(|(name, &limit)| (name.as_str(), limit)))
+            .collect::<BTreeMap<_, _>>()
+            .into_iter()
+            .map(|(name, limit)| Group {
+                name: name.to_string(),
+                limit,
+                members: Vec::new(),
+                exact: limit == GroupLimit::ExactlyOne,
+            })
+            .collect::<Vec<_>>();
+        let mut items = things
+            .iter()
+            .map(|thing| Item {
+                value: thing.value,
+                num: thing.num.map(|num| num - thing.min),
+                min: thing.min,
+                costs: thing
+                    .costs
+                    .list()
+                    .iter()
+                    .enumerate()
+                    .map(|(d, &c)| discretize(Some(&thing.name), None, d, c, !cover))
+                    .collect(),
+                group: thing
+                    .group
+                    .as_ref()
+                    .map(|group| groups.iter().position(|g| &g.name == group).unwrap()),
+                conflicts: Vec::new(),
+                requires: Vec::new(),
+                required_by: Vec::new(),
+            })
+            .collect::<Vec<_>>();
+        if !overflow.is_empty() {
+            return Err(Error::Invalid(overflow));
+        }
+        let index = |name: &String| things.iter().position(|t| &t.name == name).unwrap();
+        for (idx, thing) in things.iter().enumerate() {
+            for other in thing.conflicts.iter().map(index) {
+                items[idx].conflicts.push(other);
+                items[other].conflicts.push(idx);
+            }
+            for other in thing.requires.iter().map(index) {
+                items[idx].requires.push(other);
+                items[other].required_by.push(idx);
+            }
+        }
+        for (idx, item) in items.iter().enumerate() {
+            if let Some(group) = item.group {
+                groups[group].members.push(idx);
+            }
+        }
+        for group in groups.iter_mut() {
+            if let Some(&taken) = group.members.iter().find(|&&m| items[m].min > 0) {
+                group.exact = false;
+                for &m in group.members.iter().filter(|&&m| m != taken) {
+                    items[m].num = Some(0);
+                }
+            }
+        }
+        let mut infeasible = Vec::new();
+        for (d, cost) in costs.iter_mut().enumerate() {
+            let required = items
+                .iter()
+                .map(|item| item.costs[d].saturating_mul(item.min))
+                .fold(0, usize::saturating_add);
+            if cover {
+                *cost = cost.saturating_sub(required);
+            } else if required > *cost {
+                infeasible.push(ValidationError::Infeasible {
+                    dimension: Dimension::of(d, &dimensions),
+                    required: things
+                        .iter()
+                        .map(|t| t.costs.list()[d] * t.min as f64)
+                        .sum(),
+                    capacity: capacity[d],
+                });
+            } else {
+                *cost -= required;
+            }
+        }
+        if !infeasible.is_empty() {
+            return Err(Error::Invalid(infeasible));
+        }
+        let mut by_name = (0..things.len()).collect::<Vec<_>>();
+        by_name.sort_by(|&a, &b| things[a].name.cmp(&things[b].name));
+        let objectives = things
+            .iter()
+            .flat_map(|thing| thing.objectives.keys().cloned())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+        Ok(Self {
+            things,
+            capacity,
+            knapsacks,
+            mode,
+            bins,
+            rounding,
+            items,
+            groups,
+            costs: Costs::new(costs),
+            by_name,
+            objectives,
+            dimensions,
+        })
+    }
+
+    /// The aggregated efficiency of an item: its value per unit of cost, summed over the
+    /// dimensions as fractions of each capacity.
+    pub(crate) fn efficiency(&self, item: usize) -> f64 {
+        let item = &self.items[item];
+        let weight = item
+            .costs
+            .iter()
+            .zip(self.costs.bounds())
+            .map(|(&c, &cap)| c as f64 / cap.max(1) as f64)
+            .sum();
+        ratio(item.value, weight)
+    }
+
+    /// Whether some things conflict with or require other things.
+    pub(crate) fn has_relations(&self) -> bool {
+        self.things
+            .iter()
+            .any(|thing| !thing.conflicts.is_empty() || !thing.requires.is_empty())
+    }
+
+    /// Start building a problem.
+    pub fn builder() -> ProblemBuilder {
+        ProblemBuilder::default()
+    }
+
+    /// The things of the problem.
+    pub fn things(&self) -> &[Thing] {
+        &self.things
+    }
+
+    /// The capacity of each dimension, summed over the knapsacks if any.
+    pub fn costs(&self) -> &[f64] {
+        &self.capacity
+    }
+
+    /// The knapsacks to pack the things into, empty for a single knapsack of capacity
+    /// [`Problem::costs`].
+    pub fn knapsacks(&self) -> &[Knapsack] {
+        &self.knapsacks
+    }
+
+    /// Whether the problem packs things under its capacities or covers its requirements.
+    pub fn mode(&self) -> Mode {
+        self.mode
+    }
+
+    /// The names of the dimensions in the order of the capacities and costs, sorted, or none if
+    /// the dimensions are not named.
+    pub fn dimensions(&self) -> &[String] {
+        &self.dimensions
+    }
+
+    /// The names of the objectives given to the things, sorted.
+    pub fn objectives(&self) -> &[String] {
+        &self.objectives
+    }
+
+    /// The costs that are not whole numbers of units and have been rounded.
+    pub fn rounding(&self) -> &[Rounding] {
+        &self.rounding
+    }
+}
+
+/// Builder of [`Problem`].
+#[derive(Debug, Default)]
+pub struct ProblemBuilder {
+    problem: UncheckedProblem,
+}
+
+impl ProblemBuilder {
+    /// Set the capacity of each dimension.
+    pub fn costs(mut self, costs: impl Into<Amounts>) -> Self {
+        self.problem.costs = costs.into();
+        self
+    }
+
+    /// Cover the costs as requirements at the lowest value, instead of packing under them.
+    pub fn mode(mut self, mode: Mode) -> Self {
+        self.problem.mode = mode;
+        self
+    }
+
+    /// Set the number of integral units per unit of each dimension.
+    pub fn scale(mut self, scale: impl Into<Amounts>) -> Self {
+        self.problem.scale = Some(scale.into());
+        self
+    }
+
+    /// Add a thing.
+    pub fn thing(mut self, thing: Thing) -> Self {
+        self.problem.things.push(thing);
+        self
+    }
+
+    /// Add things.
+    pub fn things(mut self, things: impl IntoIterator<Item = Thing>) -> Self {
+        self.problem.things.extend(things);
+        self
+    }
+
+    /// Add a knapsack, to pack the things into several knapsacks instead of a single one.
+    pub fn knapsack(mut self, knapsack: Knapsack) -> Self {
+        self.problem.knapsacks.push(knapsack);
+        self
+    }
+
+    /// Limit how many members of a group can be taken.
+    pub fn group(mut self, group: impl Into<String>, limit: GroupLimit) -> Self {
+        self.problem.groups.insert(group.into(), limit);
+        self
+    }
+
+    /// Validate and build the problem.
+    pub fn build(self) -> Result<Problem> {
+        self.problem.check()
+    }
+}
+
+/// Slack allowed when comparing values, which are sums of floating-point numbers.
+pub(crate) fn tolerance(value: f64) -> f64 {
+    value.abs().max(1.0) * 1e-9
+}
+
+/// Value per unit of cost, treating free things as infinitely efficient.
+pub(crate) fn ratio(value: f64, cost: f64) -> f64 {
+    if value <= 0.0 {
+        f64::NEG_INFINITY
+    } else if cost == 0.0 {
+        f64::INFINITY
+    } else {
+        value / cost
+    }
+}
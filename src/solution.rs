@@ -0,0 +1,204 @@
+use crate::{problem::Rounding, Amounts, Problem};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// An optimal selection of things.
+///
+/// Only `value` and `chosen` are required when reading a solution back, the other fields are
+/// derived from them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Solution {
+    /// The total value of the chosen things.
+    pub value: f64,
+    /// The optimum of the LP relaxation of the problem, an upper bound of the value of any
+    /// selection, or a lower bound when covering.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bound: Option<f64>,
+    /// The distance between the value and the bound, in percent of the larger of them.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gap: Option<f64>,
+    /// Whether the solution is proven to be what was asked for, e.g. optimal.
+    ///
+    /// It is not when found by a heuristic or by a search stopped early.
+    #[serde(default)]
+    pub proven: bool,
+    /// How much of each dimension the chosen things consume, by name if the dimensions are named.
+    #[serde(default)]
+    pub used: Amounts,
+    /// How much of each dimension is left unused.
+    #[serde(default)]
+    pub remaining: Amounts,
+    /// The percentage of each dimension that is used.
+    #[serde(default)]
+    pub utilisation: Amounts,
+    /// How many copies of each thing are chosen, by name.
+    pub chosen: BTreeMap<String, usize>,
+    /// The members taken in each group, by name.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub groups: BTreeMap<String, Vec<String>>,
+    /// The total of each objective over the chosen things, when the things have objectives.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub objectives: BTreeMap<String, f64>,
+    /// The contents of each knapsack, by name, when the problem has knapsacks.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub knapsacks: BTreeMap<String, Packing>,
+    /// The costs rounded to whole units when solving.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rounding: Vec<Rounding>,
+}
+
+impl Solution {
+    /// Build the solution of the given `value` choosing `counts[i]` copies of the `i`-th thing
+    /// of the problem beyond its minimum quantity.
+    pub(crate) fn new(problem: &Problem, mut value: f64, counts: &[usize]) -> Self {
+        let counts = problem
+            .items
+            .iter()
+            .zip(counts)
+            .map(|(item, num)| {
+                value += item.value * item.min as f64;
+                item.min + num
+            })
+            .collect::<Vec<_>>();
+        let capacity = problem.costs();
+        let mut used = vec![0.0; capacity.len()];
+        for (thing, &num) in problem.things.iter().zip(&counts) {
+            for (u, c) in used.iter_mut().zip(thing.costs.list()) {
+                *u += c * num as f64;
+            }
+        }
+        let names = &problem.dimensions;
+        let remaining = Amounts::name(remaining(capacity, &used), names);
+        let utilisation = Amounts::name(utilisation(capacity, &used), names);
+        let used = Amounts::name(used, names);
+        let chosen = problem
+            .things
+            .iter()
+            .zip(&counts)
+            .map(|(thing, num)| (thing.name.clone(), *num))
+            .collect();
+        let groups = problem
+            .groups
+            .iter()
+            .map(|group| {
+                let taken = group.members.iter().filter(|&&m| counts[m] > 0);
+                let names = taken.map(|&m| problem.things[m].name.clone()).collect();
+                (group.name.clone(), names)
+            })
+            .collect();
+        let objectives = problem
+            .objectives
+            .iter()
+            .map(|objective| {
+                let total = problem
+                    .things
+                    .iter()
+                    .zip(&counts)
+                    .filter_map(|(thing, &num)| Some(thing.objectives.get(objective)? * num as f64))
+                    .sum();
+                (objective.clone(), total)
+            })
+            .collect();
+        Self {
+            value,
+            bound: None,
+            gap: None,
+            proven: true,
+            used,
+            remaining,
+            utilisation,
+            chosen,
+            groups,
+            objectives,
+            knapsacks: BTreeMap::new(),
+            rounding: problem.rounding.clone(),
+        }
+    }
+
+    /// Record the bound of the value of the problem and the gap of the solution to it.
+    pub(crate) fn with_bound(mut self, bound: Option<f64>) -> Self {
+        self.bound = bound;
+        self.gap = bound.map(|bound| {
+            let scale = bound.abs().max(self.value.abs());
+            if scale == 0.0 {
+                0.0
+            } else {
+                (bound - self.value).abs() / scale * 100.0
+            }
+        });
+        self
+    }
+
+    /// Record the contents of the knapsacks, `assignment[i][j]` copies of the `i`-th thing of
+    /// the problem going into its `j`-th knapsack.
+    pub(crate) fn with_assignment(mut self, problem: &Problem, assignment: &[Vec<usize>]) -> Self {
+        self.knapsacks = problem
+            .knapsacks
+            .iter()
+            .enumerate()
+            .map(|(j, knapsack)| {
+                let chosen = problem
+                    .things
+                    .iter()
+                    .zip(assignment)
+                    .filter(|(_, copies)| copies[j] > 0)
+                    .map(|(thing, copies)| (thing.name.clone(), copies[j]))
+                    .collect();
+                let packing = Packing::new(problem, knapsack.costs.list(), chosen);
+                (knapsack.name.clone(), packing)
+            })
+            .collect();
+        self
+    }
+}
+
+/// The contents of a knapsack.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Packing {
+    /// How much of each dimension the things in the knapsack consume.
+    #[serde(default)]
+    pub used: Amounts,
+    /// How much of each dimension is left unused.
+    #[serde(default)]
+    pub remaining: Amounts,
+    /// The percentage of each dimension that is used.
+    #[serde(default)]
+    pub utilisation: Amounts,
+    /// How many copies of each thing go into the knapsack, by name.
+    pub chosen: BTreeMap<String, usize>,
+}
+
+impl Packing {
+    fn new(problem: &Problem, capacity: &[f64], chosen: BTreeMap<String, usize>) -> Self {
+        let mut used = vec![0.0; capacity.len()];
+        for thing in problem.things.iter() {
+            let num = chosen.get(&thing.name).copied().unwrap_or(0);
+            for (u, c) in used.iter_mut().zip(thing.costs.list()) {
+                *u += c * num as f64;
+            }
+        }
+        let names = &problem.dimensions;
+        Self {
+            remaining: Amounts::name(remaining(capacity, &used), names),
+            utilisation: Amounts::name(utilisation(capacity, &used), names),
+            used: Amounts::name(used, names),
+            chosen,
+        }
+    }
+}
+
+fn remaining(capacity: &[f64], used: &[f64]) -> Vec<f64> {
+    capacity
+        .iter()
+        .zip(used)
+        .map(|(c, u)| (c - u).max(0.0))
+        .collect()
+}
+
+fn utilisation(capacity: &[f64], used: &[f64]) -> Vec<f64> {
+    capacity
+        .iter()
+        .zip(used)
+        .map(|(&c, &u)| if c == 0.0 { 0.0 } else { u / c * 100.0 })
+        .collect()
+}
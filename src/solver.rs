@@ -0,0 +1,277 @@
+use crate::{
+    bnb::{BranchAndBound, Keep},
+    dp::Dp,
+    heuristic::Heuristic,
+    sparse::Sparse,
+    Error, Mode, Problem, Result, Solution, TieBreak,
+};
+use std::{
+    fmt,
+    str::FromStr,
+    sync::atomic::{AtomicBool, Ordering},
+    time::Instant,
+};
+
+/// The algorithm used to solve a problem.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Algorithm {
+    /// Dynamic programming if its table fits in the memory budget, sparse dynamic programming
+    /// if its states do and it takes little work, branch-and-bound otherwise.
+    ///
+    /// Covering problems are only solved by dynamic programming.
+    #[default]
+    Auto,
+    /// Dynamic programming over the whole cost space.
+    Dp,
+    /// Dynamic programming over the non-dominated reachable states only.
+    SparseDp,
+    /// Branch-and-bound with LP relaxation bounds.
+    BranchAndBound,
+    /// Greedy by aggregated efficiency, fast but not optimal.
+    Greedy,
+    /// Greedy improved by add, drop and swap moves, fast but not optimal.
+    LocalSearch,
+}
+
+impl Algorithm {
+    /// Names accepted by [`Algorithm::from_str`].
+    pub const VARIANTS: &'static [&'static str] = &[
+        "auto",
+        "dp",
+        "sparse-dp",
+        "branch-and-bound",
+        "greedy",
+        "local-search",
+    ];
+}
+
+impl fmt::Display for Algorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Auto => "auto",
+            Self::Dp => "dp",
+            Self::SparseDp => "sparse-dp",
+            Self::BranchAndBound => "branch-and-bound",
+            Self::Greedy => "greedy",
+            Self::LocalSearch => "local-search",
+        };
+        f.write_str(name)
+    }
+}
+
+impl FromStr for Algorithm {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "auto" => Ok(Self::Auto),
+            "dp" => Ok(Self::Dp),
+            "sparse-dp" => Ok(Self::SparseDp),
+            "branch-and-bound" => Ok(Self::BranchAndBound),
+            "greedy" => Ok(Self::Greedy),
+            "local-search" => Ok(Self::LocalSearch),
+            _ => Err(Error::UnknownAlgorithm(s.to_string())),
+        }
+    }
+}
+
+/// The most comparisons of states [`Algorithm::Auto`] lets the sparse DP make before falling
+/// back to branch-and-bound.
+const SPARSE_WORK: usize = 1 << 24;
+
+/// Options of [`solve_with`].
+#[derive(Debug, Clone)]
+pub struct Options {
+    /// The algorithm to use.
+    pub algorithm: Algorithm,
+    /// The memory budget of the DP table, or of the states of the sparse DP, in bytes.
+    ///
+    /// [`Algorithm::Auto`] falls back to the next algorithm beyond it, and [`Algorithm::Dp`] and
+    /// [`Algorithm::SparseDp`] fail.
+    pub max_memory: usize,
+    /// How to choose between selections of the same value, which requires branch-and-bound.
+    ///
+    /// Without it, the solution is any of the optimal selections.
+    pub tie_break: Option<TieBreak>,
+    /// When branch-and-bound and local search stop, returning the best selection found so far.
+    ///
+    /// Such a selection is not proven optimal. The sparse DP stops too, without any selection,
+    /// and the other algorithms run to completion.
+    pub deadline: Option<Instant>,
+    /// A flag stopping the search the same way once set, e.g. on Ctrl-C.
+    pub interrupt: Option<&'static AtomicBool>,
+    /// The number of threads used by dynamic programming, branch-and-bound and local search.
+    ///
+    /// The solutions do not depend on it, unless the search is stopped early.
+    pub jobs: usize,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            algorithm: Algorithm::Auto,
+            max_memory: 1 << 30,
+            tie_break: None,
+            deadline: None,
+            interrupt: None,
+            jobs: 1,
+        }
+    }
+}
+
+/// When a search stops early, from the [`Options`] it is run with.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct Stop {
+    deadline: Option<Instant>,
+    interrupt: Option<&'static AtomicBool>,
+}
+
+impl Stop {
+    pub(crate) fn new(options: &Options) -> Self {
+        Self {
+            deadline: options.deadline,
+            interrupt: options.interrupt,
+        }
+    }
+
+    /// Whether the search must stop now.
+    pub(crate) fn is_due(&self) -> bool {
+        self.interrupt
+            .is_some_and(|interrupt| interrupt.load(Ordering::Relaxed))
+            || self
+                .deadline
+                .is_some_and(|deadline| Instant::now() >= deadline)
+    }
+}
+
+/// Find an optimal solution of the problem with the given options, with the bound of the LP
+/// relaxation.
+pub fn solve_with(problem: &Problem, options: &Options) -> Result<Solution> {
+    Ok(solve_unbounded(problem, options)?.with_bound(problem.lp_bound()))
+}
+
+fn solve_unbounded(problem: &Problem, options: &Options) -> Result<Solution> {
+    if options.tie_break.is_some() {
+        return enumerate(problem, Keep::Best(1), options, "tie-break")?
+            .pop()
+            .ok_or(Error::NoSolution);
+    }
+    let memory = Dp::memory(problem);
+    let fits = memory.is_some_and(|memory| memory <= options.max_memory);
+    match memory {
+        Some(memory) => info!("dp needs {} bytes", memory),
+        None => info!("dp needs more than {} bytes", usize::MAX),
+    }
+    let simple = !problem.has_relations() && problem.knapsacks.is_empty();
+    let algorithm = match options.algorithm {
+        Algorithm::Auto if fits && (simple || problem.mode == Mode::Cover) => Algorithm::Dp,
+        Algorithm::Auto if problem.mode == Mode::Cover => Algorithm::SparseDp,
+        Algorithm::Auto if simple => {
+            info!("trying {}", Algorithm::SparseDp);
+            let sparse = Sparse::new(problem, options.max_memory, Stop::new(options));
+            match sparse.with_work(SPARSE_WORK).solve() {
+                Ok(solution) => return solution.ok_or(Error::NoSolution),
+                // Branch-and-bound stops at once if the search is stopped indeed.
+                Err(Error::OutOfMemory { .. } | Error::Stopped) => Algorithm::BranchAndBound,
+                Err(err) => return Err(err),
+            }
+        }
+        Algorithm::Auto => Algorithm::BranchAndBound,
+        algorithm => algorithm,
+    };
+    info!("solving with {}", algorithm);
+    match algorithm {
+        Algorithm::Dp | Algorithm::SparseDp | Algorithm::Greedy | Algorithm::LocalSearch
+            if problem.has_relations() =>
+        {
+            Err(Error::Unsupported {
+                algorithm,
+                feature: "conflicts and requirements",
+            })
+        }
+        Algorithm::Dp | Algorithm::SparseDp | Algorithm::Greedy | Algorithm::LocalSearch
+            if !problem.knapsacks.is_empty() =>
+        {
+            Err(Error::Unsupported {
+                algorithm,
+                feature: "multiple knapsacks",
+            })
+        }
+        Algorithm::BranchAndBound | Algorithm::Greedy | Algorithm::LocalSearch
+            if problem.mode == Mode::Cover =>
+        {
+            Err(Error::Unsupported {
+                algorithm,
+                feature: "covering",
+            })
+        }
+        Algorithm::Dp if !fits => Err(Error::OutOfMemory {
+            required: memory,
+            max_memory: options.max_memory,
+        }),
+        Algorithm::Dp => Dp::new(problem, options.max_memory, options.jobs)
+            .solve()
+            .ok_or(Error::NoSolution),
+        Algorithm::SparseDp => Sparse::new(problem, options.max_memory, Stop::new(options))
+            .solve()?
+            .ok_or(Error::NoSolution),
+        Algorithm::Greedy => Heuristic::new(problem, Stop::default())
+            .solve(false, 1)
+            .ok_or(Error::NoSolution),
+        Algorithm::LocalSearch => Heuristic::new(problem, Stop::new(options))
+            .solve(true, options.jobs)
+            .ok_or(Error::NoSolution),
+        Algorithm::Auto | Algorithm::BranchAndBound => {
+            BranchAndBound::new(problem, Keep::Best(1), None, Stop::new(options))
+                .solve(options.jobs)?
+                .pop()
+                .ok_or(Error::NoSolution)
+        }
+    }
+}
+
+/// Find the `k` best distinct selections of the problem, best first.
+pub fn solve_top_with(problem: &Problem, k: usize, options: &Options) -> Result<Vec<Solution>> {
+    enumerate(problem, Keep::Best(k), options, "top-k")
+}
+
+/// Find every optimal selection of the problem, up to `limit` of them, best first according to
+/// the tie-break policy.
+pub fn solve_optimal_with(
+    problem: &Problem,
+    limit: usize,
+    options: &Options,
+) -> Result<Vec<Solution>> {
+    enumerate(
+        problem,
+        Keep::Optimal(limit),
+        options,
+        "enumerating optimal solutions",
+    )
+}
+
+/// Only branch-and-bound can enumerate alternatives, so [`Algorithm::Auto`] uses it.
+fn enumerate(
+    problem: &Problem,
+    keep: Keep,
+    options: &Options,
+    feature: &'static str,
+) -> Result<Vec<Solution>> {
+    match options.algorithm {
+        Algorithm::Auto | Algorithm::BranchAndBound if problem.mode == Mode::Cover => {
+            Err(Error::Unsupported {
+                algorithm: Algorithm::BranchAndBound,
+                feature: "covering",
+            })
+        }
+        Algorithm::Auto | Algorithm::BranchAndBound => {
+            info!("solving {:?} with {}", keep, Algorithm::BranchAndBound);
+            let stop = Stop::new(options);
+            let solutions =
+                BranchAndBound::new(problem, keep, options.tie_break, stop).solve(options.jobs)?;
+            let bound = problem.lp_bound();
+            Ok(solutions.into_iter().map(|s| s.with_bound(bound)).collect())
+        }
+        algorithm => Err(Error::Unsupported { algorithm, feature }),
+    }
+}
@@ -0,0 +1,283 @@
+use crate::{solver::Stop, Error, Mode, Problem, Result, Solution};
+use std::{cmp::Ordering, mem};
+
+/// Dynamic programming over the reachable states only, dropping the dominated ones.
+///
+/// A state is the amount of each dimension used, or still to meet when covering, with the best
+/// value reaching it. A state is dominated by another with no more of any dimension and at least
+/// its value, so that only the Pareto front of the states is kept after each pass, which is far
+/// smaller than the whole cost space for most problems.
+///
+/// When covering, the values are negated as by the dense DP.
+///
+/// Dropping the dominated states compares each state with the kept ones, which takes far longer
+/// than the states take memory when there are many of them, so the work can be limited too. The
+/// DP fails with [`Error::Stopped`] past that limit, as when it is stopped.
+#[derive(Debug)]
+pub(crate) struct Sparse<'a> {
+    problem: &'a Problem,
+    cover: bool,
+    states: Vec<State>,
+    /// The copies taken to reach the states, each step pointing back to the previous one.
+    steps: Vec<Step>,
+    max_memory: usize,
+    stop: Stop,
+    /// The comparisons of states left to make, without limit if `None`.
+    work: Option<usize>,
+}
+
+#[derive(Debug, Clone)]
+struct State {
+    amounts: Vec<usize>,
+    value: f64,
+    /// The last step taken to reach the state.
+    step: Option<usize>,
+    /// The item and copies taken by the current pass, recorded as a step if the state is kept.
+    taken: Option<(usize, usize)>,
+}
+
+#[derive(Debug)]
+struct Step {
+    prev: Option<usize>,
+    item: usize,
+    copies: usize,
+}
+
+impl<'a> Sparse<'a> {
+    /// A DP failing with [`Error::OutOfMemory`] once its states and steps exceed `max_memory`
+    /// bytes.
+    pub(crate) fn new(problem: &'a Problem, max_memory: usize, stop: Stop) -> Self {
+        let cover = problem.mode == Mode::Cover;
+        let bounds = problem.costs.bounds();
+        let start = State {
+            amounts: if cover {
+                bounds.to_vec()
+            } else {
+                vec![0; bounds.len()]
+            },
+            value: 0.0,
+            step: None,
+            taken: None,
+        };
+        Self {
+            problem,
+            cover,
+            states: vec![start],
+            steps: Vec::new(),
+            max_memory,
+            stop,
+            work: None,
+        }
+    }
+
+    /// Limit the number of comparisons of states.
+    pub(crate) fn with_work(mut self, work: usize) -> Self {
+        self.work = Some(work);
+        self
+    }
+
+    /// The state reached from `state` by taking `copies` copies of `item`, `None` if they do
+    /// not fit.
+    fn take(&self, state: &State, item: usize, copies: usize) -> Option<State> {
+        let bounds = self.problem.costs.bounds();
+        let thing = &self.problem.items[item];
+        let amounts = state
+            .amounts
+            .iter()
+            .zip(&thing.costs)
+            .zip(bounds)
+            .map(|((&a, &c), &b)| {
+                if self.cover {
+                    Some(a.saturating_sub(c.saturating_mul(copies)))
+                } else {
+                    c.checked_mul(copies)
+                        .and_then(|c| a.checked_add(c))
+                        .filter(|&a| a <= b)
+                }
+            })
+            .collect::<Option<Vec<_>>>()?;
+        let value = thing.value * copies as f64;
+        Some(State {
+            amounts,
+            value: state.value + if self.cover { -value } else { value },
+            step: state.step,
+            taken: Some((item, copies)),
+        })
+    }
+
+    /// The most copies of `item` worth trying at once: those fitting in the capacity, or
+    /// needed to meet every requirement when covering, though at least one for the groups that
+    /// must take a member.
+    fn useful(&self, item: usize) -> usize {
+        let thing = &self.problem.items[item];
+        let most = thing
+            .costs
+            .iter()
+            .zip(self.problem.costs.bounds())
+            .filter(|(&c, _)| c > 0)
+            .map(|(&c, &b)| {
+                if self.cover {
+                    b.div_ceil(c).max(1)
+                } else {
+                    b / c
+                }
+            })
+            .reduce(|a, b| if self.cover { a.max(b) } else { a.min(b) });
+        match (thing.num, most) {
+            (Some(num), Some(most)) => num.min(most),
+            (None, Some(most)) => most,
+            (Some(num), None) => num,
+            // Free copies without limit are worth nothing, or the problem is unbounded.
+            (None, None) => 1,
+        }
+    }
+
+    /// Take `item` in any quantity, by chunks of doubling size as the dense DP does.
+    fn item_pass(&mut self, item: usize) -> Result<()> {
+        let mut num = self.useful(item);
+        let mut k = 1;
+        while num > 0 {
+            let copies = k.min(num);
+            let taken = self
+                .states
+                .iter()
+                .filter_map(|state| self.take(state, item, copies))
+                .collect::<Vec<_>>();
+            self.states.extend(taken);
+            self.prune()?;
+            num -= copies;
+            k *= 2;
+        }
+        Ok(())
+    }
+
+    /// Take at most one of the `members`, or exactly one if `exact`, in any quantity.
+    fn group_pass(&mut self, members: &[usize], exact: bool) -> Result<()> {
+        let mut next = Vec::new();
+        for state in self.states.iter() {
+            for &m in members {
+                let most = self.useful(m);
+                // Free copies only add to the value when packing, so that all of them are taken.
+                let free = self.problem.items[m].costs.iter().all(|&c| c == 0);
+                let first = if free && !self.cover { most.max(1) } else { 1 };
+                for k in first..=most {
+                    let taken = match self.take(state, m, k) {
+                        Some(taken) => taken,
+                        None => break,
+                    };
+                    // When covering, a copy that meets nothing more only adds to the value.
+                    let previous = next.last().map(|s: &State| &s.amounts);
+                    if self.cover && k > 1 && previous == Some(&taken.amounts) {
+                        break;
+                    }
+                    next.push(taken);
+                }
+            }
+        }
+        if exact {
+            self.states = next;
+        } else {
+            self.states.extend(next);
+        }
+        self.prune()
+    }
+
+    /// Drop the dominated states and record the steps of the kept ones.
+    fn prune(&mut self) -> Result<()> {
+        let mut states = mem::take(&mut self.states);
+        states.sort_by(|a, b| {
+            b.value
+                .partial_cmp(&a.value)
+                .unwrap_or(Ordering::Equal)
+                .then_with(|| a.amounts.cmp(&b.amounts))
+        });
+        // Each state is only dominated by the states before it, of at least its value.
+        let mut least = usize::MAX;
+        for (i, mut state) in states.into_iter().enumerate() {
+            let work = if state.amounts.len() == 1 {
+                1
+            } else {
+                self.states.len()
+            };
+            let left = self.work.map(|left| left.checked_sub(work));
+            if left == Some(None) || (i.is_multiple_of(1024) && self.stop.is_due()) {
+                info!("sparse dp stopped with {} states", self.states.len());
+                return Err(Error::Stopped);
+            }
+            self.work = left.flatten();
+            let dominated = match state.amounts[..] {
+                [a] => {
+                    let dominated = a >= least;
+                    least = least.min(a);
+                    dominated
+                }
+                _ => self
+                    .states
+                    .iter()
+                    .any(|kept| kept.amounts.iter().zip(&state.amounts).all(|(k, a)| k <= a)),
+            };
+            if dominated {
+                continue;
+            }
+            if let Some((item, copies)) = state.taken.take() {
+                self.steps.push(Step {
+                    prev: state.step,
+                    item,
+                    copies,
+                });
+                state.step = Some(self.steps.len() - 1);
+            }
+            self.states.push(state);
+        }
+        // Each state holds its own amount of each dimension.
+        let state = mem::size_of::<State>() + mem::size_of_val(self.problem.costs.bounds());
+        let memory = self.states.len() * state + self.steps.len() * mem::size_of::<Step>();
+        if memory > self.max_memory {
+            return Err(Error::OutOfMemory {
+                required: Some(memory),
+                max_memory: self.max_memory,
+            });
+        }
+        Ok(())
+    }
+
+    /// Solve the problem, `None` if no selection satisfies the groups.
+    pub(crate) fn solve(mut self) -> Result<Option<Solution>> {
+        let problem = self.problem;
+        for (idx, item) in problem.items.iter().enumerate() {
+            if item.group.is_none() {
+                self.item_pass(idx)?;
+            }
+        }
+        for group in problem.groups.iter() {
+            self.group_pass(&group.members, group.exact)?;
+        }
+        info!(
+            "sparse dp kept {} states and {} steps",
+            self.states.len(),
+            self.steps.len()
+        );
+        // The states are sorted by decreasing value.
+        let best = match self
+            .states
+            .iter()
+            .find(|state| !self.cover || state.amounts.iter().all(|&a| a == 0))
+        {
+            Some(best) => best,
+            None => return Ok(None),
+        };
+        let mut chosen = vec![0; problem.items.len()];
+        let mut step = best.step;
+        while let Some(s) = step {
+            let Step { prev, item, copies } = self.steps[s];
+            chosen[item] += copies;
+            step = prev;
+        }
+        let value = if self.cover {
+            0.0 - best.value
+        } else {
+            best.value
+        };
+        Ok(Some(Solution::new(problem, value, &chosen)))
+    }
+}
@@ -0,0 +1,74 @@
+use crate::{Error, Problem, Result};
+use std::{cmp::Ordering, fmt, str::FromStr};
+
+/// How to choose between selections of the same value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TieBreak {
+    /// Prefer the selection with fewer copies in total.
+    FewerItems,
+    /// Prefer the selection using less of the capacities, summed as fractions of each capacity.
+    LessResource,
+    /// Prefer the selection with more copies of the things whose names come first.
+    Lexicographic,
+}
+
+impl TieBreak {
+    /// Names accepted by [`TieBreak::from_str`].
+    pub const VARIANTS: &'static [&'static str] =
+        &["fewer-items", "less-resource", "lexicographic"];
+
+    /// Compare two selections of the problem, `Ordering::Less` if `a` is preferred.
+    pub(crate) fn compare(self, problem: &Problem, a: &[usize], b: &[usize]) -> Ordering {
+        match self {
+            Self::FewerItems => a.iter().sum::<usize>().cmp(&b.iter().sum()),
+            Self::LessResource => {
+                let resource = |counts: &[usize]| -> f64 {
+                    problem
+                        .things
+                        .iter()
+                        .zip(counts)
+                        .flat_map(|(thing, &num)| {
+                            thing
+                                .costs
+                                .list()
+                                .iter()
+                                .zip(&problem.capacity)
+                                .map(move |(c, cap)| c * num as f64 / cap)
+                        })
+                        .sum()
+                };
+                resource(a).total_cmp(&resource(b))
+            }
+            Self::Lexicographic => problem
+                .by_name
+                .iter()
+                .map(|&i| b[i].cmp(&a[i]))
+                .find(|o| o.is_ne())
+                .unwrap_or(Ordering::Equal),
+        }
+    }
+}
+
+impl fmt::Display for TieBreak {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::FewerItems => "fewer-items",
+            Self::LessResource => "less-resource",
+            Self::Lexicographic => "lexicographic",
+        };
+        f.write_str(name)
+    }
+}
+
+impl FromStr for TieBreak {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "fewer-items" => Ok(Self::FewerItems),
+            "less-resource" => Ok(Self::LessResource),
+            "lexicographic" => Ok(Self::Lexicographic),
+            _ => Err(Error::UnknownTieBreak(s.to_string())),
+        }
+    }
+}
@@ -0,0 +1,254 @@
+use crate::{problem::tolerance, Amounts, Dimension, GroupLimit, Mode, Problem, Solution};
+use serde::Serialize;
+
+/// A way in which a solution does not satisfy its problem.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+pub enum Violation {
+    /// A chosen thing is not part of the problem.
+    UnknownThing { name: String },
+    /// A knapsack of the solution is not part of the problem.
+    UnknownKnapsack { name: String },
+    /// Fewer copies of a thing are chosen than its minimum quantity.
+    TooFew {
+        name: String,
+        count: usize,
+        min: usize,
+    },
+    /// More copies of a thing are chosen than are available.
+    TooMany {
+        name: String,
+        count: usize,
+        num: usize,
+    },
+    /// The number of members taken in a group exceeds or falls short of its limit.
+    GroupLimit {
+        group: String,
+        limit: GroupLimit,
+        taken: Vec<String>,
+    },
+    /// The copies of a thing in the knapsacks do not add up to the copies chosen.
+    AssignmentMismatch {
+        name: String,
+        chosen: usize,
+        assigned: usize,
+    },
+    /// Two conflicting things are both taken.
+    Conflict { thing: String, other: String },
+    /// A thing is taken without a thing it requires.
+    MissingRequirement { thing: String, requires: String },
+    /// The chosen things consume more than the capacity of a dimension.
+    OverCapacity {
+        dimension: Dimension,
+        used: f64,
+        capacity: f64,
+    },
+    /// The chosen things fall short of the requirement of a dimension.
+    UnderRequirement {
+        dimension: Dimension,
+        used: f64,
+        required: f64,
+    },
+    /// The things in a knapsack consume more than its capacity in a dimension.
+    OverKnapsack {
+        knapsack: String,
+        dimension: Dimension,
+        used: f64,
+        capacity: f64,
+    },
+    /// The value claimed by the solution is not the value of its chosen things.
+    ValueMismatch { claimed: f64, actual: f64 },
+}
+
+impl Violation {
+    /// The exit code bit of the kind of violation, so that several kinds can be reported at once.
+    pub fn code(&self) -> i32 {
+        match self {
+            Self::UnknownThing { .. } | Self::UnknownKnapsack { .. } => 2,
+            Self::TooFew { .. }
+            | Self::TooMany { .. }
+            | Self::AssignmentMismatch { .. }
+            | Self::GroupLimit { .. }
+            | Self::Conflict { .. }
+            | Self::MissingRequirement { .. } => 4,
+            Self::OverCapacity { .. }
+            | Self::UnderRequirement { .. }
+            | Self::OverKnapsack { .. } => 8,
+            Self::ValueMismatch { .. } => 16,
+        }
+    }
+}
+
+/// The result of checking a solution against a problem.
+#[derive(Debug, Clone, Serialize)]
+pub struct Verification {
+    /// Whether the solution satisfies the problem.
+    pub valid: bool,
+    /// The value of the chosen things.
+    pub value: f64,
+    /// How much of each dimension the chosen things consume, by name if the dimensions are named.
+    pub used: Amounts,
+    /// Everything wrong with the solution.
+    pub violations: Vec<Violation>,
+}
+
+impl Verification {
+    /// The exit code reporting the kinds of violations found, `0` if the solution is valid.
+    pub fn code(&self) -> i32 {
+        self.violations.iter().fold(0, |code, v| code | v.code())
+    }
+}
+
+impl Problem {
+    /// Re-compute the value and usage of the things chosen by a solution, and check them against
+    /// the problem.
+    pub fn verify(&self, solution: &Solution) -> Verification {
+        let mut violations = Vec::new();
+        for name in solution.chosen.keys() {
+            if !self.things.iter().any(|thing| &thing.name == name) {
+                violations.push(Violation::UnknownThing { name: name.clone() });
+            }
+        }
+        let mut value = 0.0;
+        let mut used = vec![0.0; self.capacity.len()];
+        for thing in self.things.iter() {
+            let count = solution.chosen.get(&thing.name).copied().unwrap_or(0);
+            if count < thing.min {
+                violations.push(Violation::TooFew {
+                    name: thing.name.clone(),
+                    count,
+                    min: thing.min,
+                });
+            }
+            if let Some(num) = thing.num.filter(|num| count > *num) {
+                violations.push(Violation::TooMany {
+                    name: thing.name.clone(),
+                    count,
+                    num,
+                });
+            }
+            value += thing.value * count as f64;
+            for (u, c) in used.iter_mut().zip(thing.costs.list()) {
+                *u += c * count as f64;
+            }
+        }
+        let is_taken = |name: &String| solution.chosen.get(name).is_some_and(|c| *c > 0);
+        for group in self.groups.iter() {
+            let taken = group
+                .members
+                .iter()
+                .map(|&m| &self.things[m].name)
+                .filter(|name| is_taken(name))
+                .cloned()
+                .collect::<Vec<_>>();
+            let valid = match group.limit {
+                GroupLimit::AtMostOne => taken.len() <= 1,
+                GroupLimit::ExactlyOne => taken.len() == 1,
+            };
+            if !valid {
+                violations.push(Violation::GroupLimit {
+                    group: group.name.clone(),
+                    limit: group.limit,
+                    taken,
+                });
+            }
+        }
+        for thing in self.things.iter().filter(|thing| is_taken(&thing.name)) {
+            for other in thing.conflicts.iter().filter(|other| is_taken(other)) {
+                violations.push(Violation::Conflict {
+                    thing: thing.name.clone(),
+                    other: other.clone(),
+                });
+            }
+            for other in thing.requires.iter().filter(|other| !is_taken(other)) {
+                violations.push(Violation::MissingRequirement {
+                    thing: thing.name.clone(),
+                    requires: other.clone(),
+                });
+            }
+        }
+        violations.extend(self.verify_knapsacks(solution));
+        for (d, (&used, &capacity)) in used.iter().zip(&self.capacity).enumerate() {
+            let dimension = Dimension::of(d, &self.dimensions);
+            if self.mode == Mode::Cover {
+                if used < capacity - tolerance(capacity) {
+                    violations.push(Violation::UnderRequirement {
+                        dimension,
+                        used,
+                        required: capacity,
+                    });
+                }
+            } else if used > capacity + tolerance(capacity) {
+                violations.push(Violation::OverCapacity {
+                    dimension,
+                    used,
+                    capacity,
+                });
+            }
+        }
+        if (solution.value - value).abs() > tolerance(value) {
+            violations.push(Violation::ValueMismatch {
+                claimed: solution.value,
+                actual: value,
+            });
+        }
+        Verification {
+            valid: violations.is_empty(),
+            value,
+            used: Amounts::name(used, &self.dimensions),
+            violations,
+        }
+    }
+
+    /// Check the contents of the knapsacks of a solution, if the problem has knapsacks.
+    fn verify_knapsacks(&self, solution: &Solution) -> Vec<Violation> {
+        let mut violations = Vec::new();
+        for name in solution.knapsacks.keys() {
+            if !self.knapsacks.iter().any(|knapsack| &knapsack.name == name) {
+                violations.push(Violation::UnknownKnapsack { name: name.clone() });
+            }
+        }
+        if self.knapsacks.is_empty() {
+            return violations;
+        }
+        for thing in self.things.iter() {
+            let chosen = solution.chosen.get(&thing.name).copied().unwrap_or(0);
+            let assigned = solution
+                .knapsacks
+                .values()
+                .filter_map(|packing| packing.chosen.get(&thing.name))
+                .sum();
+            if chosen != assigned {
+                violations.push(Violation::AssignmentMismatch {
+                    name: thing.name.clone(),
+                    chosen,
+                    assigned,
+                });
+            }
+        }
+        for knapsack in self.knapsacks.iter() {
+            let packing = match solution.knapsacks.get(&knapsack.name) {
+                Some(packing) => packing,
+                None => continue,
+            };
+            for (d, &capacity) in knapsack.costs.list().iter().enumerate() {
+                let used = self
+                    .things
+                    .iter()
+                    .filter_map(|thing| {
+                        Some(thing.costs.list()[d] * *packing.chosen.get(&thing.name)? as f64)
+                    })
+                    .sum::<f64>();
+                if used > capacity + tolerance(capacity) {
+                    violations.push(Violation::OverKnapsack {
+                        knapsack: knapsack.name.clone(),
+                        dimension: Dimension::of(d, &self.dimensions),
+                        used,
+                        capacity,
+                    });
+                }
+            }
+        }
+        violations
+    }
+}
@@ -0,0 +1,14 @@
+//! Helpers shared by the integration tests.
+
+/// A linear congruential generator, enough to draw reproducible problems.
+pub struct Lcg(pub u64);
+
+impl Lcg {
+    pub fn below(&mut self, n: u64) -> u64 {
+        self.0 = self
+            .0
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        (self.0 >> 33) % n
+    }
+}
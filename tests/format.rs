@@ -0,0 +1,50 @@
+use mkp::{Format, Problem, Solution, Thing};
+use std::collections::BTreeMap;
+
+fn named(amounts: &[(&str, f64)]) -> BTreeMap<String, f64> {
+    amounts.iter().map(|&(d, a)| (d.to_string(), a)).collect()
+}
+
+/// Take more copies of `A` than there are, write the solution and its check as TOML and read them back.
+fn round_trip(problem: &Problem) {
+    let mut solution = mkp::solve(problem).unwrap();
+    solution.chosen.insert("A".to_string(), 4);
+    let toml = Format::Toml.serialize(&solution).unwrap();
+    let solution = Format::Toml.deserialize::<Solution>(&toml).unwrap();
+    let verification = problem.verify(&solution);
+    assert!(!verification.valid);
+    assert_eq!(verification.code() & 4, 4);
+    let toml = Format::Toml.serialize(&verification).unwrap();
+    let value = Format::Toml.deserialize::<toml::Value>(&toml).unwrap();
+    assert_eq!(value["valid"].as_bool(), Some(false));
+    let kinds = value["violations"].as_array().unwrap();
+    assert!(kinds.iter().any(|v| v["kind"].as_str() == Some("too-many")));
+    assert!(value["used"].is_array() || value["used"].is_table());
+}
+
+#[test]
+fn toml_round_trip_with_positional_dimensions() {
+    let problem = Problem::builder()
+        .costs(vec![10.0, 20.0])
+        .thing(Thing::new("A", 1.5, 3, vec![2.0, 1.0]))
+        .thing(Thing::new("B", 4.0, 2, vec![5.0, 4.0]))
+        .build()
+        .unwrap();
+    round_trip(&problem);
+}
+
+#[test]
+fn toml_round_trip_with_named_dimensions() {
+    let problem = Problem::builder()
+        .costs(named(&[("weight", 10.0), ("volume", 20.0)]))
+        .thing(Thing::new("A", 1.5, 3, named(&[("weight", 2.0)])))
+        .thing(Thing::new(
+            "B",
+            4.0,
+            2,
+            named(&[("weight", 5.0), ("volume", 4.0)]),
+        ))
+        .build()
+        .unwrap();
+    round_trip(&problem);
+}
@@ -0,0 +1,191 @@
+mod common;
+
+use common::Lcg;
+use mkp::{Amounts, Dimension, Error, Knapsack, Problem, Thing, Violation};
+
+/// The best value over every way of spreading the copies of the things over the knapsacks,
+/// `None` if the minimum quantities cannot be placed.
+fn brute_force(problem: &Problem) -> Option<f64> {
+    let things = problem.things();
+    let knapsacks = problem.knapsacks();
+    // Every split of the copies of each thing between the knapsacks, within its limits.
+    let splits = things
+        .iter()
+        .map(|thing| {
+            let num = thing.num.unwrap();
+            let mut splits = vec![Vec::new()];
+            for _ in knapsacks {
+                splits = splits
+                    .into_iter()
+                    .flat_map(|split: Vec<usize>| {
+                        let taken = split.iter().sum::<usize>();
+                        (0..=num - taken).map(move |k| {
+                            let mut split = split.clone();
+                            split.push(k);
+                            split
+                        })
+                    })
+                    .collect();
+            }
+            splits.retain(|split| split.iter().sum::<usize>() >= thing.min);
+            splits
+        })
+        .collect::<Vec<_>>();
+    let mut picks = vec![0; things.len()];
+    let mut best: Option<f64> = None;
+    loop {
+        let fits = knapsacks.iter().enumerate().all(|(j, knapsack)| {
+            let capacity = list(&knapsack.costs);
+            (0..capacity.len()).all(|d| {
+                let used = things
+                    .iter()
+                    .zip(&picks)
+                    .zip(&splits)
+                    .map(|((thing, &p), splits)| list(&thing.costs)[d] * splits[p][j] as f64)
+                    .sum::<f64>();
+                used <= capacity[d]
+            })
+        });
+        if fits {
+            let value = things
+                .iter()
+                .zip(&picks)
+                .zip(&splits)
+                .map(|((thing, &p), splits)| thing.value * splits[p].iter().sum::<usize>() as f64)
+                .sum::<f64>();
+            best = Some(best.map_or(value, |best| best.max(value)));
+        }
+        let mut i = 0;
+        loop {
+            if i == picks.len() {
+                return best;
+            }
+            if picks[i] + 1 < splits[i].len() {
+                picks[i] += 1;
+                break;
+            }
+            picks[i] = 0;
+            i += 1;
+        }
+    }
+}
+
+fn list(amounts: &Amounts) -> &[f64] {
+    match amounts {
+        Amounts::List(list) => list,
+        Amounts::Named(_) => unreachable!("the amounts are listed"),
+    }
+}
+
+#[test]
+fn copies_are_spread_over_the_knapsacks_optimally() {
+    let mut rng = Lcg(15);
+    for _ in 0..200 {
+        let dimensions = 1 + rng.below(2) as usize;
+        let knapsacks = (0..2 + rng.below(2))
+            .map(|j| {
+                let costs = (0..dimensions)
+                    .map(|_| 1.0 + rng.below(8) as f64)
+                    .collect::<Vec<_>>();
+                Knapsack::new(format!("k{}", j), costs)
+            })
+            .collect::<Vec<_>>();
+        let things = (0..1 + rng.below(3))
+            .map(|i| {
+                let costs = (0..dimensions)
+                    .map(|_| rng.below(5) as f64)
+                    .collect::<Vec<_>>();
+                let num = rng.below(3) as usize;
+                let min = if rng.below(3) == 0 { num } else { 0 };
+                Thing::new(format!("t{}", i), rng.below(6) as f64, num, costs).with_min(min)
+            })
+            .collect::<Vec<_>>();
+        let mut builder = Problem::builder().things(things);
+        for knapsack in knapsacks {
+            builder = builder.knapsack(knapsack);
+        }
+        let problem = match builder.build() {
+            Ok(problem) => problem,
+            // The minimum quantities alone exceed the knapsacks together.
+            Err(Error::Invalid(_)) => continue,
+            Err(err) => panic!("{}", err),
+        };
+        let solution = match (mkp::solve(&problem), brute_force(&problem)) {
+            (Ok(solution), Some(best)) => {
+                assert_eq!(solution.value, best, "{:?}", problem);
+                solution
+            }
+            (Err(Error::NoSolution), None) => continue,
+            (result, best) => panic!("{:?}: got {:?}, expected {:?}", problem, result, best),
+        };
+        assert_eq!(solution.knapsacks.len(), problem.knapsacks().len());
+        assert_eq!(
+            problem.verify(&solution).violations,
+            vec![],
+            "{:?}",
+            problem
+        );
+    }
+}
+
+#[test]
+fn minimum_quantities_are_placed_across_knapsacks() {
+    let problem = Problem::builder()
+        .knapsack(Knapsack::new("k0", vec![5.0]))
+        .knapsack(Knapsack::new("k1", vec![5.0]))
+        .thing(Thing::new("A", 1.0, 2, vec![4.0]).with_min(2))
+        .thing(Thing::new("B", 3.0, 2, vec![1.0]))
+        .build()
+        .unwrap();
+    let solution = mkp::solve(&problem).unwrap();
+    assert_eq!(solution.value, 8.0);
+    for knapsack in ["k0", "k1"] {
+        assert_eq!(solution.knapsacks[knapsack].chosen["A"], 1);
+        assert_eq!(solution.knapsacks[knapsack].chosen["B"], 1);
+    }
+    assert_eq!(problem.verify(&solution).code(), 0);
+}
+
+#[test]
+fn verify_checks_the_contents_of_the_knapsacks() {
+    let problem = Problem::builder()
+        .knapsack(Knapsack::new("k0", vec![5.0]))
+        .knapsack(Knapsack::new("k1", vec![5.0]))
+        .thing(Thing::new("A", 1.0, 2, vec![4.0]))
+        .build()
+        .unwrap();
+    let solution = mkp::solve(&problem).unwrap();
+    assert_eq!(solution.chosen["A"], 2);
+
+    let mut mismatch = solution.clone();
+    mismatch.knapsacks.get_mut("k1").unwrap().chosen.remove("A");
+    let verification = problem.verify(&mismatch);
+    assert_eq!(
+        verification.violations,
+        vec![Violation::AssignmentMismatch {
+            name: "A".to_string(),
+            chosen: 2,
+            assigned: 1,
+        }]
+    );
+    assert_eq!(verification.code(), 4);
+
+    let mut over = solution;
+    over.knapsacks.get_mut("k1").unwrap().chosen.remove("A");
+    over.knapsacks
+        .get_mut("k0")
+        .unwrap()
+        .chosen
+        .insert("A".to_string(), 2);
+    let verification = problem.verify(&over);
+    assert_eq!(
+        verification.violations,
+        vec![Violation::OverKnapsack {
+            knapsack: "k0".to_string(),
+            dimension: Dimension::Index(0),
+            used: 8.0,
+            capacity: 5.0,
+        }]
+    );
+    assert_eq!(verification.code(), 8);
+}
@@ -0,0 +1,162 @@
+mod common;
+
+use common::Lcg;
+use mkp::{Algorithm, Mode, Options, Problem, Thing};
+
+/// The optimum of a single dimension relaxed, taking the things by efficiency, best first when
+/// packing and cheapest first when covering, `None` if the requirement cannot be met.
+fn fractional(capacity: f64, things: &[(f64, f64, usize)], cover: bool) -> Option<f64> {
+    let mut things = things.to_vec();
+    // Free things are worth taking whole when packing, and meet nothing when covering.
+    let efficiency = |&(value, cost, _): &(f64, f64, usize)| {
+        if cost == 0.0 {
+            f64::INFINITY
+        } else {
+            value / cost
+        }
+    };
+    things.sort_by(|a, b| efficiency(b).partial_cmp(&efficiency(a)).unwrap());
+    if cover {
+        things.reverse();
+        things.retain(|&(_, cost, _)| cost > 0.0);
+    }
+    let (mut left, mut value) = (capacity, 0.0);
+    for (v, cost, num) in things {
+        if left <= 0.0 {
+            break;
+        }
+        let taken = if cost == 0.0 {
+            num as f64
+        } else {
+            (left / cost).min(num as f64)
+        };
+        left -= taken * cost;
+        value += taken * v;
+    }
+    if cover && left > 0.0 {
+        None
+    } else {
+        Some(value)
+    }
+}
+
+fn close(a: f64, b: f64) -> bool {
+    (a - b).abs() <= a.abs().max(1.0) * 1e-6
+}
+
+#[test]
+fn single_dimension_takes_the_fractional_optimum() {
+    let mut rng = Lcg(18);
+    for round in 0..500 {
+        let cover = round % 2 == 1;
+        let capacity = 1 + rng.below(40);
+        let things = (0..1 + rng.below(6))
+            .map(|_| {
+                let cost = rng.below(10) as f64;
+                // Free things are only bounded in value when their copies are limited.
+                let cost = if cover { cost } else { cost.max(1.0) };
+                (rng.below(20) as f64, cost, 1 + rng.below(6) as usize)
+            })
+            .collect::<Vec<_>>();
+        let problem = Problem::builder()
+            .mode(if cover { Mode::Cover } else { Mode::Pack })
+            .costs(vec![capacity as f64])
+            .things(things.iter().enumerate().map(|(i, &(value, cost, num))| {
+                Thing::new(format!("t{}", i), value, num, vec![cost])
+            }))
+            .build()
+            .unwrap();
+        let expected = fractional(capacity as f64, &things, cover);
+        match (problem.lp_bound(), expected) {
+            (Some(bound), Some(expected)) => assert!(close(bound, expected), "{:?}", problem),
+            (bound, expected) => assert_eq!(bound, expected, "{:?}", problem),
+        }
+    }
+}
+
+#[test]
+fn covering_out_of_reach_has_no_bound() {
+    let problem = Problem::builder()
+        .mode(Mode::Cover)
+        .costs(vec![10.0, 5.0])
+        .thing(Thing::new("A", 1.0, 2, vec![3.0, 5.0]))
+        .thing(Thing::new("B", 1.0, 5, vec![0.0, 1.0]))
+        .build()
+        .unwrap();
+    assert_eq!(problem.lp_bound(), None);
+}
+
+#[test]
+fn limited_copies_bound_the_relaxation() {
+    // Without the limit, the capacity would be filled with A alone for a bound of 100.
+    let problem = Problem::builder()
+        .costs(vec![10.0, 10.0])
+        .thing(Thing::new("A", 10.0, 2, vec![1.0, 1.0]))
+        .thing(Thing::new("B", 1.0, 100, vec![1.0, 2.0]))
+        .build()
+        .unwrap();
+    assert!(close(problem.lp_bound().unwrap(), 24.0));
+    let cover = Problem::builder()
+        .mode(Mode::Cover)
+        .costs(vec![10.0])
+        .thing(Thing::new("A", 1.0, 2, vec![5.0]))
+        .thing(Thing::new("B", 4.0, 10, vec![5.0]))
+        .build()
+        .unwrap();
+    assert!(close(cover.lp_bound().unwrap(), 2.0));
+    let short = Problem::builder()
+        .mode(Mode::Cover)
+        .costs(vec![10.0])
+        .thing(Thing::new("A", 1.0, 1, vec![5.0]))
+        .thing(Thing::new("B", 4.0, 10, vec![5.0]))
+        .build()
+        .unwrap();
+    assert!(close(short.lp_bound().unwrap(), 5.0));
+}
+
+#[test]
+fn bound_holds_the_optimum_of_several_dimensions() {
+    let mut rng = Lcg(81);
+    let dp = Options {
+        algorithm: Algorithm::Dp,
+        ..Options::default()
+    };
+    for round in 0..300 {
+        let cover = round % 2 == 1;
+        let dimensions = 2 + rng.below(2) as usize;
+        let costs = (0..dimensions)
+            .map(|_| 1.0 + rng.below(15) as f64)
+            .collect::<Vec<_>>();
+        let things = (0..1 + rng.below(6))
+            .map(|i| {
+                let costs = (0..dimensions)
+                    .map(|_| rng.below(6) as f64)
+                    .collect::<Vec<_>>();
+                let value = rng.below(20) as f64;
+                if rng.below(4) == 0 && costs.iter().any(|&c| c > 0.0) {
+                    Thing::unbounded(format!("t{}", i), value, costs)
+                } else {
+                    Thing::new(format!("t{}", i), value, rng.below(5) as usize, costs)
+                }
+            })
+            .collect::<Vec<_>>();
+        let problem = Problem::builder()
+            .mode(if cover { Mode::Cover } else { Mode::Pack })
+            .costs(costs)
+            .things(things)
+            .build()
+            .unwrap();
+        let (bound, solution) = (problem.lp_bound(), mkp::solve_with(&problem, &dp));
+        match (bound, solution) {
+            (Some(bound), Ok(solution)) if cover => {
+                assert!(bound <= solution.value + 1e-6, "{:?}", problem)
+            }
+            (Some(bound), Ok(solution)) => {
+                assert!(bound >= solution.value - 1e-6, "{:?}", problem)
+            }
+            // Nothing covers the requirements when their relaxation cannot.
+            (None, Err(mkp::Error::NoSolution)) if cover => {}
+            (bound, solution) => panic!("{:?}: {:?} and {:?}", problem, bound, solution),
+        }
+    }
+}
@@ -0,0 +1,68 @@
+use mkp::{Dimension, Error, Problem, Thing, ValidationError};
+
+#[test]
+fn amounts_too_large_once_scaled_are_invalid() {
+    let result = Problem::builder()
+        .costs(vec![1e30])
+        .thing(Thing::new("A", 1.0, 1, vec![1e28]))
+        .thing(Thing::new("B", 1.0, 1, vec![3e27]))
+        .build();
+    let errors = match result {
+        Err(Error::Invalid(errors)) => errors,
+        other => panic!("expected an invalid problem, got {:?}", other),
+    };
+    assert_eq!(errors.len(), 3);
+    assert!(errors
+        .iter()
+        .all(|e| matches!(e, ValidationError::AmountOverflow { .. })));
+}
+
+#[test]
+fn every_fault_is_reported_with_its_thing() {
+    let result = Problem::builder()
+        .costs(vec![10.0, 0.0])
+        .thing(Thing::new("A", 1.0, 1, vec![1.0, 0.0]))
+        .thing(Thing::new("A", 2.0, 1, vec![1.0, 0.0]))
+        .thing(Thing::new("B", f64::NAN, 1, vec![1.0, 0.0]))
+        .thing(Thing::new("C", -1.0, 1, vec![1.0, 0.0]))
+        .thing(Thing::new("D", 1.0, 1, vec![1.0]))
+        .build();
+    let mut errors = match result {
+        Err(Error::Invalid(errors)) => errors,
+        other => panic!("expected an invalid problem, got {:?}", other),
+    };
+    // NaN equals nothing, not even itself.
+    let nan = errors.remove(2);
+    assert!(
+        matches!(
+            &nan,
+            ValidationError::InvalidValue { index: 2, name, value } if name == "B" && value.is_nan()
+        ),
+        "{:?}",
+        nan
+    );
+    assert_eq!(
+        errors,
+        vec![
+            ValidationError::ZeroCapacity {
+                dimension: Dimension::Index(1),
+            },
+            ValidationError::DuplicateName {
+                index: 1,
+                name: "A".to_string(),
+                first: 0,
+            },
+            ValidationError::InvalidValue {
+                index: 3,
+                name: "C".to_string(),
+                value: -1.0,
+            },
+            ValidationError::CostsMismatch {
+                index: 4,
+                name: "D".to_string(),
+                expected: 2,
+                actual: 1,
+            },
+        ]
+    );
+}
@@ -0,0 +1,144 @@
+mod common;
+
+use common::Lcg;
+use mkp::{Amounts, Error, Problem, Relation, Thing, ValidationError};
+
+#[test]
+fn cycles_and_unknown_references_are_reported() {
+    let result = Problem::builder()
+        .costs(vec![10.0])
+        .thing(Thing::new("A", 1.0, 1, vec![1.0]).requires("A"))
+        .thing(Thing::new("B", 1.0, 1, vec![1.0]).requires("C"))
+        .thing(Thing::new("C", 1.0, 1, vec![1.0]).requires("B"))
+        .thing(Thing::new("D", 1.0, 1, vec![1.0]).requires("E"))
+        .thing(Thing::new("E", 1.0, 1, vec![1.0]).requires("F"))
+        .thing(Thing::new("F", 1.0, 1, vec![1.0]).requires("D"))
+        .thing(
+            Thing::new("G", 1.0, 1, vec![1.0])
+                .conflicts_with("nowhere")
+                .requires("gone"),
+        )
+        .build();
+    let errors = match result {
+        Err(Error::Invalid(errors)) => errors,
+        other => panic!("expected an invalid problem, got {:?}", other),
+    };
+    let cycle = |things: &[&str]| ValidationError::RequirementCycle {
+        things: things.iter().map(|t| t.to_string()).collect(),
+    };
+    let unknown = |relation, reference: &str| ValidationError::UnknownReference {
+        index: 6,
+        name: "G".to_string(),
+        relation,
+        reference: reference.to_string(),
+    };
+    assert_eq!(
+        errors,
+        vec![
+            unknown(Relation::Conflicts, "nowhere"),
+            unknown(Relation::Requires, "gone"),
+            cycle(&["A"]),
+            cycle(&["B", "C"]),
+            cycle(&["D", "E", "F"]),
+        ]
+    );
+}
+
+/// A small problem whose things may conflict with another and require an earlier one, so that
+/// the requirements have no cycle.
+fn related(rng: &mut Lcg) -> Problem {
+    let n = 2 + rng.below(4) as usize;
+    let things = (0..n).map(|i| {
+        let costs = vec![rng.below(5) as f64, rng.below(5) as f64];
+        let mut thing = Thing::new(
+            format!("t{}", i),
+            rng.below(6) as f64,
+            rng.below(3) as usize,
+            costs,
+        );
+        if rng.below(3) == 0 {
+            let other = (i + 1 + rng.below(n as u64 - 1) as usize) % n;
+            thing = thing.conflicts_with(format!("t{}", other));
+        }
+        if i > 0 && rng.below(3) == 0 {
+            thing = thing.requires(format!("t{}", rng.below(i as u64)));
+        }
+        thing
+    });
+    let things = things.collect::<Vec<_>>();
+    Problem::builder()
+        .costs(vec![1.0 + rng.below(10) as f64, 1.0 + rng.below(10) as f64])
+        .things(things)
+        .build()
+        .unwrap()
+}
+
+/// The best value over every selection respecting the capacity, the conflicts and the
+/// requirements.
+fn brute_force(problem: &Problem) -> f64 {
+    let things = problem.things();
+    let index = |name: &String| things.iter().position(|t| &t.name == name).unwrap();
+    let mut counts = vec![0; things.len()];
+    let mut best = 0.0f64;
+    loop {
+        let fits = problem.costs().iter().enumerate().all(|(d, &capacity)| {
+            let used = things
+                .iter()
+                .zip(&counts)
+                .map(|(t, &k)| list(&t.costs)[d] * k as f64)
+                .sum::<f64>();
+            used <= capacity
+        });
+        let related = things.iter().zip(&counts).all(|(t, &k)| {
+            k == 0
+                || (t.conflicts.iter().all(|o| counts[index(o)] == 0)
+                    && t.requires.iter().all(|o| counts[index(o)] > 0))
+        });
+        if fits && related {
+            let value = things
+                .iter()
+                .zip(&counts)
+                .map(|(t, &k)| t.value * k as f64)
+                .sum::<f64>();
+            best = best.max(value);
+        }
+        let mut i = 0;
+        loop {
+            if i == counts.len() {
+                return best;
+            }
+            if counts[i] < things[i].num.unwrap() {
+                counts[i] += 1;
+                break;
+            }
+            counts[i] = 0;
+            i += 1;
+        }
+    }
+}
+
+fn list(amounts: &Amounts) -> &[f64] {
+    match amounts {
+        Amounts::List(list) => list,
+        Amounts::Named(_) => unreachable!("the amounts are listed"),
+    }
+}
+
+#[test]
+fn solutions_respect_conflicts_and_requirements() {
+    let mut rng = Lcg(14);
+    for _ in 0..300 {
+        let problem = related(&mut rng);
+        let solution = mkp::solve(&problem).unwrap();
+        assert_eq!(solution.value, brute_force(&problem), "{:?}", problem);
+        assert_eq!(
+            problem.verify(&solution).violations,
+            vec![],
+            "{:?}",
+            problem
+        );
+        for solution in mkp::solve_optimal_with(&problem, 100, &Default::default()).unwrap() {
+            assert_eq!(problem.verify(&solution).code(), 0, "{:?}", problem);
+        }
+    }
+}
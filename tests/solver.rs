@@ -0,0 +1,335 @@
+mod common;
+
+use common::Lcg;
+use mkp::{Algorithm, Amounts, Error, GroupLimit, Mode, Options, Problem, Thing};
+use std::time::Instant;
+
+/// A problem of 4 wide dimensions whose values follow the costs, so that a great many states
+/// are not dominated.
+fn correlated(seed: u64) -> Problem {
+    let mut rng = Lcg(seed);
+    let things = (0..12).map(|i| {
+        let costs = (0..4)
+            .map(|_| 1.0 + rng.below(1500) as f64)
+            .collect::<Vec<_>>();
+        let value = costs.iter().sum::<f64>() + rng.below(100) as f64 / 100.0;
+        Thing::new(format!("t{}", i), value, 5 + rng.below(25) as usize, costs)
+    });
+    Problem::builder()
+        .costs(vec![5000.0; 4])
+        .things(things)
+        .build()
+        .unwrap()
+}
+
+/// A small problem of bounded, unbounded and grouped things, whose group must take a member if
+/// `exact`, so that the DP goes through chunk, complete and group passes.
+fn small(rng: &mut Lcg, mode: Mode, exact: bool) -> Problem {
+    let dimensions = 1 + rng.below(2) as usize;
+    let costs = (0..dimensions)
+        .map(|_| 1.0 + rng.below(10) as f64)
+        .collect::<Vec<_>>();
+    let things = (0..2 + rng.below(3)).map(|i| {
+        let mut costs = (0..dimensions)
+            .map(|_| rng.below(5) as f64)
+            .collect::<Vec<_>>();
+        let value = rng.below(6) as f64;
+        let thing = if rng.below(3) == 0 {
+            // Unlimited copies must cost something.
+            costs[0] = costs[0].max(1.0);
+            Thing::unbounded(format!("t{}", i), value, costs)
+        } else {
+            Thing::new(format!("t{}", i), value, rng.below(4) as usize, costs)
+        };
+        // An exact group must have a member.
+        if (exact && i == 0) || rng.below(3) == 0 {
+            thing.with_group("g")
+        } else {
+            thing
+        }
+    });
+    let things = things.collect::<Vec<_>>();
+    let limit = if exact {
+        GroupLimit::ExactlyOne
+    } else {
+        GroupLimit::AtMostOne
+    };
+    Problem::builder()
+        .mode(mode)
+        .costs(costs)
+        .things(things)
+        .group("g", limit)
+        .build()
+        .unwrap()
+}
+
+fn list(amounts: &Amounts) -> &[f64] {
+    match amounts {
+        Amounts::List(list) => list,
+        Amounts::Named(_) => unreachable!("the amounts are listed"),
+    }
+}
+
+/// The best value over every selection of at most 12 copies of each thing, `None` if none
+/// satisfies the problem.
+fn brute_force(problem: &Problem, exact: bool) -> Option<f64> {
+    let things = problem.things();
+    let cover = problem.mode() == Mode::Cover;
+    let most = things
+        .iter()
+        .map(|t| t.num.unwrap_or(12))
+        .collect::<Vec<_>>();
+    let mut counts = vec![0; things.len()];
+    let mut best: Option<f64> = None;
+    loop {
+        let fits = problem.costs().iter().enumerate().all(|(d, &bound)| {
+            let used = things
+                .iter()
+                .zip(&counts)
+                .map(|(t, &k)| list(&t.costs)[d] * k as f64)
+                .sum::<f64>();
+            if cover {
+                used >= bound
+            } else {
+                used <= bound
+            }
+        });
+        let members = things
+            .iter()
+            .zip(&counts)
+            .filter(|(t, &k)| k > 0 && t.group.is_some())
+            .count();
+        if fits && members <= 1 && (!exact || members == 1) {
+            let value = things
+                .iter()
+                .zip(&counts)
+                .map(|(t, &k)| t.value * k as f64)
+                .sum::<f64>();
+            best = Some(match best {
+                Some(best) if cover => best.min(value),
+                Some(best) => best.max(value),
+                None => value,
+            });
+        }
+        let mut i = 0;
+        loop {
+            if i == counts.len() {
+                return best;
+            }
+            if counts[i] < most[i] {
+                counts[i] += 1;
+                break;
+            }
+            counts[i] = 0;
+            i += 1;
+        }
+    }
+}
+
+#[test]
+fn dp_traces_back_optimal_selections() {
+    let mut rng = Lcg(23);
+    for round in 0..200 {
+        let mode = if round % 2 == 0 {
+            Mode::Pack
+        } else {
+            Mode::Cover
+        };
+        let exact = rng.below(2) == 0;
+        let problem = small(&mut rng, mode, exact);
+        let best = brute_force(&problem, exact);
+        let dp = Options {
+            algorithm: Algorithm::Dp,
+            ..Options::default()
+        };
+        // The least memory holds the fewest tables, so that the passes are replayed from the
+        // middle rather than all held at once.
+        let least = match mkp::solve_with(
+            &problem,
+            &Options {
+                max_memory: 0,
+                ..dp
+            },
+        ) {
+            Err(Error::OutOfMemory {
+                required: Some(required),
+                ..
+            }) => required,
+            other => panic!("expected the DP to run out of memory, got {:?}", other),
+        };
+        for options in [
+            Options {
+                max_memory: least,
+                ..dp
+            },
+            dp,
+        ] {
+            let solution = match (mkp::solve_with(&problem, &options), best) {
+                (Ok(solution), Some(_)) => solution,
+                (Err(Error::NoSolution), None) => continue,
+                (result, best) => panic!("{:?}: got {:?}, expected {:?}", problem, result, best),
+            };
+            let chosen = problem
+                .things()
+                .iter()
+                .map(|t| t.value * solution.chosen[&t.name] as f64)
+                .sum::<f64>();
+            assert_eq!(Some(solution.value), best, "{:?}", problem);
+            assert_eq!(chosen, solution.value, "{:?}", problem);
+            assert_eq!(problem.verify(&solution).code(), 0, "{:?}", problem);
+        }
+    }
+}
+
+#[test]
+fn auto_falls_back_to_branch_and_bound() {
+    let problem = correlated(4);
+    let auto = mkp::solve(&problem).unwrap();
+    let options = Options {
+        algorithm: Algorithm::BranchAndBound,
+        ..Options::default()
+    };
+    let bnb = mkp::solve_with(&problem, &options).unwrap();
+    assert!(auto.proven);
+    assert!((auto.value - bnb.value).abs() < 1e-6);
+    assert_eq!(problem.verify(&auto).code(), 0);
+}
+
+#[test]
+fn sparse_dp_stops_on_time() {
+    let problem = correlated(4);
+    let options = Options {
+        algorithm: Algorithm::SparseDp,
+        deadline: Some(Instant::now()),
+        ..Options::default()
+    };
+    assert!(matches!(
+        mkp::solve_with(&problem, &options),
+        Err(Error::Stopped)
+    ));
+}
+
+#[test]
+fn enumeration_takes_things_worth_nothing() {
+    let problem = Problem::builder()
+        .costs(vec![10.0])
+        .thing(Thing::new("A", 2.0, 1, vec![3.0]))
+        .thing(Thing::new("B", 2.0, 1, vec![3.0]))
+        .thing(Thing::new("Z", 0.0, 1, vec![1.0]))
+        .build()
+        .unwrap();
+    let options = Options::default();
+    let optimal = mkp::solve_optimal_with(&problem, 10, &options).unwrap();
+    assert_eq!(optimal.len(), 2);
+    assert!(optimal.iter().any(|s| s.chosen["Z"] == 1));
+    let top = mkp::solve_top_with(&problem, 10, &options).unwrap();
+    assert_eq!(top.len(), 8);
+}
+
+#[test]
+fn branch_and_bound_skips_hopeless_counts() {
+    let problem = Problem::builder()
+        .costs(vec![1e19])
+        .thing(Thing::unbounded("A", 3.0, vec![7.0]))
+        .build()
+        .unwrap();
+    let options = Options {
+        algorithm: Algorithm::BranchAndBound,
+        ..Options::default()
+    };
+    let solution = mkp::solve_with(&problem, &options).unwrap();
+    assert!(solution.proven);
+    assert_eq!(solution.chosen["A"], 1428571428571428571);
+}
+
+#[test]
+fn dp_takes_huge_counts_without_overflow() {
+    for mode in [Mode::Pack, Mode::Cover] {
+        let problem = Problem::builder()
+            .mode(mode)
+            .costs(vec![10.0])
+            .thing(Thing::new(
+                "A",
+                1.0,
+                1_000_000_000_000_000_000,
+                vec![1000.0],
+            ))
+            .build()
+            .unwrap();
+        for algorithm in [Algorithm::Auto, Algorithm::Dp, Algorithm::SparseDp] {
+            let options = Options {
+                algorithm,
+                ..Options::default()
+            };
+            let solution = mkp::solve_with(&problem, &options).unwrap();
+            let taken = if mode == Mode::Cover { 1 } else { 0 };
+            assert_eq!(solution.chosen["A"], taken, "{:?} {:?}", mode, algorithm);
+        }
+    }
+}
+
+#[test]
+fn free_group_members_take_every_copy_at_once() {
+    let problem = Problem::builder()
+        .costs(vec![1000.0])
+        .thing(Thing::new("A", 1.0, 100_000_000, vec![0.0]).with_group("g"))
+        .thing(Thing::new("B", 2.0, 3, vec![7.0]).with_group("g"))
+        .build()
+        .unwrap();
+    for algorithm in [Algorithm::Dp, Algorithm::SparseDp] {
+        let options = Options {
+            algorithm,
+            ..Options::default()
+        };
+        let solution = mkp::solve_with(&problem, &options).unwrap();
+        assert_eq!(solution.chosen["A"], 100_000_000, "{:?}", algorithm);
+    }
+}
+
+/// A problem of 3 dimensions and things of few distinct values, so that many selections tie,
+/// wide enough for the DP to split its passes over threads.
+fn tied(rng: &mut Lcg) -> Problem {
+    let things = (0..8).map(|i| {
+        let costs = (0..3).map(|_| rng.below(12) as f64).collect::<Vec<_>>();
+        let value = 1.0 + rng.below(3) as f64;
+        Thing::new(format!("t{}", i), value, 1 + rng.below(4) as usize, costs)
+    });
+    let things = things.collect::<Vec<_>>();
+    Problem::builder()
+        .costs(vec![63.0; 3])
+        .things(things)
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn selections_do_not_depend_on_the_threads() {
+    let mut rng = Lcg(24);
+    for _ in 0..2 {
+        let problem = tied(&mut rng);
+        for algorithm in [
+            Algorithm::Dp,
+            Algorithm::BranchAndBound,
+            Algorithm::LocalSearch,
+        ] {
+            let [one, four] = [1, 4].map(|jobs| {
+                let options = Options {
+                    algorithm,
+                    jobs,
+                    ..Options::default()
+                };
+                mkp::solve_with(&problem, &options).unwrap().chosen
+            });
+            assert_eq!(one, four, "{:?} {:?}", algorithm, problem);
+        }
+        let [one, four] = [1, 4].map(|jobs| {
+            let options = Options {
+                jobs,
+                ..Options::default()
+            };
+            let top = mkp::solve_top_with(&problem, 5, &options).unwrap();
+            top.into_iter().map(|s| s.chosen).collect::<Vec<_>>()
+        });
+        assert_eq!(one, four, "{:?}", problem);
+    }
+}
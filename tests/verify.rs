@@ -0,0 +1,186 @@
+use mkp::{Dimension, GroupLimit, Knapsack, Mode, Problem, Solution, Thing, Violation};
+
+/// A problem whose things have a minimum, a group, a conflict and a requirement to violate.
+fn constrained() -> Problem {
+    Problem::builder()
+        .costs(vec![10.0])
+        .thing(Thing::new("A", 1.0, 2, vec![5.0]).with_min(1))
+        .thing(Thing::new("B", 1.0, 1, vec![1.0]).with_group("g"))
+        .thing(
+            Thing::new("C", 1.0, 1, vec![1.0])
+                .with_group("g")
+                .conflicts_with("D"),
+        )
+        .thing(Thing::new("D", 1.0, 1, vec![1.0]))
+        .thing(Thing::new("E", 1.0, 1, vec![1.0]).requires("F"))
+        .thing(Thing::new("F", 1.0, 1, vec![1.0]))
+        .group("g", GroupLimit::AtMostOne)
+        .build()
+        .unwrap()
+}
+
+/// A solution of the problem choosing `chosen` and claiming `value`.
+fn claim(problem: &Problem, chosen: &[(&str, usize)], value: f64) -> Solution {
+    let mut solution = mkp::solve(problem).unwrap();
+    solution.chosen = chosen.iter().map(|&(t, k)| (t.to_string(), k)).collect();
+    solution.value = value;
+    solution
+}
+
+fn name(name: &str) -> String {
+    name.to_string()
+}
+
+#[test]
+fn each_violation_sets_its_bit() {
+    let problem = constrained();
+    let cases = vec![
+        (vec![("A", 1)], 1.0, vec![], 0),
+        (
+            vec![("A", 1), ("Z", 1)],
+            1.0,
+            vec![Violation::UnknownThing { name: name("Z") }],
+            2,
+        ),
+        (
+            vec![],
+            0.0,
+            vec![Violation::TooFew {
+                name: name("A"),
+                count: 0,
+                min: 1,
+            }],
+            4,
+        ),
+        (
+            vec![("A", 3)],
+            3.0,
+            vec![
+                Violation::TooMany {
+                    name: name("A"),
+                    count: 3,
+                    num: 2,
+                },
+                Violation::OverCapacity {
+                    dimension: Dimension::Index(0),
+                    used: 15.0,
+                    capacity: 10.0,
+                },
+            ],
+            12,
+        ),
+        (
+            vec![("A", 1), ("B", 1), ("C", 1)],
+            3.0,
+            vec![Violation::GroupLimit {
+                group: name("g"),
+                limit: GroupLimit::AtMostOne,
+                taken: vec![name("B"), name("C")],
+            }],
+            4,
+        ),
+        (
+            vec![("A", 1), ("C", 1), ("D", 1)],
+            3.0,
+            vec![Violation::Conflict {
+                thing: name("C"),
+                other: name("D"),
+            }],
+            4,
+        ),
+        (
+            vec![("A", 1), ("E", 1), ("F", 0)],
+            2.0,
+            vec![Violation::MissingRequirement {
+                thing: name("E"),
+                requires: name("F"),
+            }],
+            4,
+        ),
+        (
+            vec![("A", 2), ("D", 1)],
+            3.0,
+            vec![Violation::OverCapacity {
+                dimension: Dimension::Index(0),
+                used: 11.0,
+                capacity: 10.0,
+            }],
+            8,
+        ),
+        (
+            vec![("A", 1)],
+            2.0,
+            vec![Violation::ValueMismatch {
+                claimed: 2.0,
+                actual: 1.0,
+            }],
+            16,
+        ),
+        (
+            vec![("A", 2), ("C", 1), ("D", 1), ("Z", 1)],
+            0.0,
+            vec![
+                Violation::UnknownThing { name: name("Z") },
+                Violation::Conflict {
+                    thing: name("C"),
+                    other: name("D"),
+                },
+                Violation::OverCapacity {
+                    dimension: Dimension::Index(0),
+                    used: 12.0,
+                    capacity: 10.0,
+                },
+                Violation::ValueMismatch {
+                    claimed: 0.0,
+                    actual: 4.0,
+                },
+            ],
+            30,
+        ),
+    ];
+    for (chosen, value, violations, code) in cases {
+        let verification = problem.verify(&claim(&problem, &chosen, value));
+        assert_eq!(verification.violations, violations, "{:?}", chosen);
+        assert_eq!(verification.code(), code, "{:?}", chosen);
+        assert_eq!(verification.valid, code == 0, "{:?}", chosen);
+    }
+}
+
+#[test]
+fn covering_short_of_a_requirement_is_reported() {
+    let problem = Problem::builder()
+        .mode(Mode::Cover)
+        .costs(vec![10.0])
+        .thing(Thing::new("A", 1.0, 5, vec![3.0]))
+        .build()
+        .unwrap();
+    let verification = problem.verify(&claim(&problem, &[("A", 3)], 3.0));
+    assert_eq!(
+        verification.violations,
+        vec![Violation::UnderRequirement {
+            dimension: Dimension::Index(0),
+            used: 9.0,
+            required: 10.0,
+        }]
+    );
+    assert_eq!(verification.code(), 8);
+    assert_eq!(verification.value, 3.0);
+}
+
+#[test]
+fn unknown_knapsacks_are_reported() {
+    let problem = Problem::builder()
+        .knapsack(Knapsack::new("k0", vec![5.0]))
+        .thing(Thing::new("A", 1.0, 1, vec![4.0]))
+        .build()
+        .unwrap();
+    let mut solution = mkp::solve(&problem).unwrap();
+    let packing = solution.knapsacks.remove("k0").unwrap();
+    solution.knapsacks.insert(name("k9"), packing);
+    let verification = problem.verify(&solution);
+    assert_eq!(
+        verification.violations,
+        vec![Violation::UnknownKnapsack { name: name("k9") }]
+    );
+    assert_eq!(verification.code(), 2);
+}